@@ -0,0 +1,306 @@
+use reki::git::{ApplyOutcome, Error, Oid, RefKind, Result};
+
+use super::App;
+use crate::format::list_paths;
+use crate::prompt::{Prompt, PromptKind};
+
+/// A repository-changing operation, run once the user confirms it.
+pub enum Action {
+    CreateBranch {
+        name: String,
+        target: Oid,
+    },
+    CreateTag {
+        name: String,
+        target: Oid,
+        /// Annotated tag message; `None` for a lightweight tag.
+        message: Option<String>,
+    },
+    RenameBranch {
+        from: String,
+        to: String,
+    },
+    DeleteBranch {
+        name: String,
+    },
+    /// Detach HEAD at a commit.
+    CheckoutCommit {
+        target: Oid,
+    },
+    CheckoutBranch {
+        name: String,
+    },
+    /// Apply a commit's changes onto HEAD; `mainline` selects the parent
+    /// for merge commits.
+    CherryPick {
+        target: Oid,
+        mainline: u32,
+    },
+    /// Commit the inverse of a commit's changes onto HEAD.
+    Revert {
+        target: Oid,
+        mainline: u32,
+    },
+    /// Run the planned interactive rebase of this many commits.
+    Rebase {
+        commits: usize,
+    },
+    /// Abandon the paused interactive rebase.
+    AbortRebase,
+    /// Apply `stash@{index}` to the working tree.
+    StashApply {
+        index: usize,
+    },
+    /// Apply `stash@{index}` and drop it.
+    StashPop {
+        index: usize,
+    },
+    StashDrop {
+        index: usize,
+    },
+    /// Abandon a cherry-pick or revert that stopped with these conflicts.
+    AbortApply {
+        conflicts: Vec<String>,
+    },
+}
+
+impl Action {
+    /// Short description used in the confirmation prompt.
+    pub fn describe(&self) -> String {
+        match self {
+            Action::CreateBranch { name, target } => {
+                format!("Create branch '{name}' at {target:.7}")
+            }
+            Action::CreateTag {
+                name,
+                target,
+                message: None,
+            } => format!("Create tag '{name}' at {target:.7}"),
+            Action::CreateTag {
+                name,
+                target,
+                message: Some(_),
+            } => format!("Create annotated tag '{name}' at {target:.7}"),
+            Action::RenameBranch { from, to } => format!("Rename branch '{from}' to '{to}'"),
+            Action::DeleteBranch { name } => format!("Delete branch '{name}'"),
+            Action::CheckoutCommit { target } => format!("Check out {target:.7} (detached HEAD)"),
+            Action::CheckoutBranch { name } => format!("Check out branch '{name}'"),
+            Action::CherryPick { target, .. } => format!("Cherry-pick {target:.7} onto HEAD"),
+            Action::Revert { target, .. } => format!("Revert {target:.7}"),
+            Action::Rebase { commits } => format!("Rebase {commits} commits as planned"),
+            Action::AbortRebase => "Abort the rebase and restore HEAD".to_string(),
+            Action::StashApply { index } => format!("Apply stash@{{{index}}}"),
+            Action::StashPop { index } => format!("Pop stash@{{{index}}}"),
+            Action::StashDrop { index } => format!("Drop stash@{{{index}}}"),
+            Action::AbortApply { conflicts } => {
+                format!(
+                    "Conflicts in {}; abort and restore the changed files",
+                    list_paths(conflicts)
+                )
+            }
+        }
+    }
+
+    /// Whether the action moves HEAD or changes the working tree, so the
+    /// log (walked from HEAD, topped by uncommitted rows) must be reloaded
+    /// rather than just redecorated.
+    fn reloads_log(&self) -> bool {
+        matches!(
+            self,
+            Action::CheckoutCommit { .. }
+                | Action::CheckoutBranch { .. }
+                | Action::StashApply { .. }
+                | Action::StashPop { .. }
+                | Action::AbortApply { .. }
+        )
+    }
+
+    /// Message shown after the action succeeded.
+    fn done(&self) -> String {
+        match self {
+            Action::CreateBranch { name, .. } => format!("Created branch '{name}'"),
+            Action::CreateTag { name, .. } => format!("Created tag '{name}'"),
+            Action::RenameBranch { to, .. } => format!("Renamed branch to '{to}'"),
+            Action::DeleteBranch { name } => format!("Deleted branch '{name}'"),
+            Action::CheckoutCommit { target } => format!("HEAD is now at {target:.7}"),
+            Action::CheckoutBranch { name } => format!("Switched to branch '{name}'"),
+            Action::CherryPick { target, .. } => format!("Cherry-picked {target:.7}"),
+            Action::Revert { target, .. } => format!("Reverted {target:.7}"),
+            Action::Rebase { .. } => "Rebased".to_string(),
+            Action::StashApply { index } => format!("Applied stash@{{{index}}}"),
+            Action::StashPop { index } => format!("Popped stash@{{{index}}}"),
+            Action::StashDrop { index } => format!("Dropped stash@{{{index}}}"),
+            Action::AbortApply { .. } | Action::AbortRebase => "Aborted".to_string(),
+        }
+    }
+}
+
+impl App {
+    /// Ask for confirmation before running `action`.
+    pub(super) fn confirm(&mut self, action: Action) {
+        self.prompt = Some(Prompt::new(PromptKind::Confirm(action)));
+    }
+
+    /// Report that the user declined to run `action`.
+    pub(super) fn decline(&mut self, action: Action) {
+        match action {
+            Action::AbortApply { .. } => {
+                self.info("Conflicts left in place; resolve them and commit with git");
+            }
+            _ => self.info("Cancelled"),
+        }
+    }
+
+    /// Run a confirmed action and report the outcome in the status bar.
+    pub(super) fn run_action(&mut self, action: Action) {
+        let result: Result<()> = match &action {
+            Action::CreateBranch { name, target } => self.repo.create_branch(name, *target),
+            Action::CreateTag {
+                name,
+                target,
+                message,
+            } => self.repo.create_tag(name, *target, message.as_deref()),
+            Action::RenameBranch { from, to } => self.repo.rename_branch(from, to),
+            Action::DeleteBranch { name } => self.repo.delete_branch(name),
+            Action::CheckoutCommit { target } => self.repo.checkout_commit(*target),
+            Action::CheckoutBranch { name } => self.repo.checkout_branch(name),
+            Action::CherryPick { .. } | Action::Revert { .. } => {
+                self.run_apply(&action);
+                return;
+            }
+            Action::Rebase { .. } => {
+                self.start_rebase();
+                return;
+            }
+            Action::AbortRebase => {
+                self.abort_rebase();
+                return;
+            }
+            Action::AbortApply { .. } => self.repo.abort_apply(),
+            Action::StashApply { index } => self.repo.stash_apply(*index),
+            Action::StashPop { index } => self.repo.stash_pop(*index),
+            Action::StashDrop { index } => self.repo.stash_drop(*index),
+        };
+        match result {
+            Ok(()) => {
+                if action.reloads_log() {
+                    self.reload();
+                }
+                if matches!(action, Action::StashPop { .. } | Action::StashDrop { .. }) {
+                    self.refresh_stash_list();
+                }
+                self.info(action.done());
+            }
+            Err(Error::WouldOverwrite(paths)) => self.error(format!(
+                "{} failed: local changes would be overwritten: {}",
+                action.describe(),
+                list_paths(&paths)
+            )),
+            Err(e) => self.error(format!("{} failed: {e}", action.describe())),
+        }
+    }
+
+    /// Start a checkout of the selected commit (`c`) or one of its branches (`C`).
+    pub(super) fn start_checkout(&mut self, key: char) {
+        let Some(c) = self.selected_commit() else {
+            return;
+        };
+        match key {
+            'c' => self.confirm(Action::CheckoutCommit { target: c.id() }),
+            'C' => {
+                let branch = self
+                    .repo
+                    .refs_for(c.id())
+                    .iter()
+                    .find(|r| matches!(r.kind, RefKind::Branch))
+                    .map(|r| r.name.clone())
+                    .unwrap_or_default();
+                self.prompt = Some(Prompt::with_input(PromptKind::CheckoutBranch, branch));
+            }
+            _ => {}
+        }
+    }
+
+    /// Cherry-pick or revert, then show the new commit at the top of the
+    /// log, or offer to abort if the changes conflict.
+    fn run_apply(&mut self, action: &Action) {
+        let result = match *action {
+            Action::CherryPick { target, mainline } => self.repo.cherry_pick(target, mainline),
+            Action::Revert { target, mainline } => self.repo.revert(target, mainline),
+            _ => return,
+        };
+        match result {
+            Ok(ApplyOutcome::Committed(id)) => {
+                self.reload();
+                self.select_oid(id);
+                self.info(format!("{} as {id:.7}", action.done()));
+            }
+            Ok(ApplyOutcome::Conflicts(conflicts)) => {
+                self.confirm(Action::AbortApply { conflicts });
+            }
+            Err(e) => self.error(format!("{} failed: {e}", action.describe())),
+        }
+    }
+
+    /// Start a cherry-pick (`p`) or revert (`v`) of the selected commit,
+    /// asking for the mainline parent first if it is a merge.
+    pub(super) fn start_apply(&mut self, key: char) {
+        let Some(c) = self.selected_commit() else {
+            return;
+        };
+        let target = c.id();
+        let revert = key == 'v';
+        let parents = c.parent_count();
+        if parents > 1 {
+            self.prompt = Some(Prompt::with_input(
+                PromptKind::Mainline {
+                    target,
+                    revert,
+                    parents,
+                },
+                "1".to_string(),
+            ));
+        } else if revert {
+            self.confirm(Action::Revert {
+                target,
+                mainline: 0,
+            });
+        } else {
+            self.confirm(Action::CherryPick {
+                target,
+                mainline: 0,
+            });
+        }
+    }
+
+    /// Start a ref edit on the selected commit (`b`, `t`, `T`, `R`, `D`).
+    pub(super) fn start_ref_edit(&mut self, key: char) {
+        let Some(c) = self.selected_commit() else {
+            return;
+        };
+        let target = c.id();
+        // Pre-fill branch prompts with a local branch on the selected commit.
+        let branch = self
+            .repo
+            .refs_for(target)
+            .iter()
+            .find(|r| matches!(r.kind, RefKind::Branch))
+            .map(|r| r.name.clone())
+            .unwrap_or_default();
+
+        self.prompt = Some(match key {
+            'b' => Prompt::new(PromptKind::CreateBranch { target }),
+            't' => Prompt::new(PromptKind::CreateTag {
+                target,
+                annotated: false,
+            }),
+            'T' => Prompt::new(PromptKind::CreateTag {
+                target,
+                annotated: true,
+            }),
+            'R' => Prompt::with_input(PromptKind::RenameBranch, branch),
+            'D' => Prompt::with_input(PromptKind::DeleteBranch, branch),
+            _ => return,
+        });
+    }
+}
@@ -0,0 +1,258 @@
+//! Compact storage for the commits loaded into the log.
+
+use std::collections::HashMap;
+
+use reki::git::{CommitInfo, GraphRow, Identity, Oid, Uncommitted};
+
+use crate::format::format_reference;
+
+/// Flag bits of a row.
+const REWRITTEN: u8 = 1;
+const STAGED: u8 = 2;
+const UNSTAGED: u8 = 4;
+
+/// The loaded log, one column per field, so that a long history costs a
+/// few bytes per commit rather than a dozen heap allocations.
+///
+/// Names are interned, summaries are packed into one buffer, graph lines
+/// are kept as their lanes and drawn when shown, and ref decorations are
+/// not stored at all: they are looked up in the repository's ref map.
+///
+/// Memory budget: a row takes 68 bytes plus its summary, about 120 MB per
+/// million commits with 50-byte summaries, however wide the graph. Columns
+/// grow by doubling, so allow up to twice that while loading. Names cost
+/// once per distinct person.
+#[derive(Default)]
+pub struct CommitList {
+    ids: Vec<Oid>,
+    parent_counts: Vec<u16>,
+    authors: Vec<Person>,
+    committers: Vec<Person>,
+    co_authors: Vec<u8>,
+    flags: Vec<u8>,
+    summaries: Packed,
+    graph: Vec<(u32, u32)>,
+    names: Names,
+    /// Widest graph line, to align the columns after it.
+    graph_width: usize,
+}
+
+/// An author or committer: interned names and the timestamp.
+#[derive(Clone, Copy)]
+struct Person {
+    /// Name mapped through `.mailmap`.
+    name: u32,
+    /// Name as recorded (the same as `name` if `.mailmap` left it alone).
+    raw_name: u32,
+    time: i64,
+}
+
+/// Strings stored back to back in one buffer. Offsets are `u32`, which
+/// caps the buffer at 4 GiB; strings past that are stored empty.
+#[derive(Default)]
+struct Packed {
+    text: String,
+    ends: Vec<u32>,
+}
+
+impl Packed {
+    fn push(&mut self, s: &str) {
+        if u32::try_from(self.text.len() + s.len()).is_ok() {
+            self.text.push_str(s);
+        }
+        self.ends.push(self.text.len() as u32);
+    }
+
+    fn get(&self, index: usize) -> &str {
+        let start = index.checked_sub(1).map_or(0, |i| self.ends[i] as usize);
+        &self.text[start..self.ends[index] as usize]
+    }
+}
+
+/// Interned names, each stored once.
+#[derive(Default)]
+struct Names {
+    names: Vec<Box<str>>,
+    index: HashMap<Box<str>, u32>,
+}
+
+impl Names {
+    fn intern(&mut self, name: &str) -> u32 {
+        if let Some(&i) = self.index.get(name) {
+            return i;
+        }
+        let i = self.names.len() as u32;
+        self.names.push(name.into());
+        self.index.insert(name.into(), i);
+        i
+    }
+}
+
+impl CommitList {
+    /// Append `c`, drawn after the graph row `graph`.
+    pub fn push(&mut self, c: &CommitInfo, graph: GraphRow) {
+        let mut person = |who: &Identity| {
+            let name = self.names.intern(&who.name);
+            let raw_name = match &who.raw {
+                Some((raw, _)) => self.names.intern(raw),
+                None => name,
+            };
+            Person {
+                name,
+                raw_name,
+                time: who.time,
+            }
+        };
+        let (author, committer) = (person(&c.author), person(&c.committer));
+
+        let mut flags = 0;
+        if c.rewritten() {
+            flags |= REWRITTEN;
+        }
+        flags |= match c.uncommitted {
+            Some(Uncommitted::Staged) => STAGED,
+            Some(Uncommitted::Unstaged) => UNSTAGED,
+            None => 0,
+        };
+
+        self.ids.push(c.id);
+        self.parent_counts
+            .push(u16::try_from(c.parent_ids.len()).unwrap_or(u16::MAX));
+        self.authors.push(author);
+        self.committers.push(committer);
+        self.co_authors
+            .push(u8::try_from(c.co_author_count()).unwrap_or(u8::MAX));
+        self.flags.push(flags);
+        self.summaries.push(&c.summary);
+        let lane = |n: usize| u32::try_from(n).unwrap_or(u32::MAX);
+        self.graph.push((lane(graph.column), lane(graph.lanes)));
+        self.graph_width = self.graph_width.max(graph.width());
+    }
+
+    /// Drop every row, keeping the interned names.
+    pub fn clear(&mut self) {
+        let names = std::mem::take(&mut self.names);
+        *self = Self {
+            names,
+            ..Self::default()
+        };
+    }
+
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether no rows are loaded.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Row `index`, if loaded.
+    pub fn get(&self, index: usize) -> Option<Row<'_>> {
+        (index < self.len()).then_some(Row { list: self, index })
+    }
+
+    /// Rows from `start` (clamped to the end) to before `end`.
+    pub fn range(&self, start: usize, end: usize) -> impl Iterator<Item = Row<'_>> {
+        let end = end.min(self.len());
+        (start.min(end)..end).map(|index| Row { list: self, index })
+    }
+
+    /// Every row, top to bottom.
+    pub fn iter(&self) -> impl Iterator<Item = Row<'_>> {
+        self.range(0, self.len())
+    }
+
+    /// Index of commit `id` at or after `start`.
+    pub fn position(&self, id: Oid, start: usize) -> Option<usize> {
+        let start = start.min(self.len());
+        self.ids[start..]
+            .iter()
+            .position(|&i| i == id)
+            .map(|i| start + i)
+    }
+
+    /// Length of the longest graph line as a string.
+    pub fn graph_width(&self) -> usize {
+        self.graph_width
+    }
+}
+
+/// One row of a [`CommitList`].
+#[derive(Clone, Copy)]
+pub struct Row<'a> {
+    list: &'a CommitList,
+    index: usize,
+}
+
+impl<'a> Row<'a> {
+    /// Id of the commit, or [`Uncommitted::id`] for a pseudo-commit row.
+    pub fn id(&self) -> Oid {
+        self.list.ids[self.index]
+    }
+
+    /// Number of parents, saturating at `u16::MAX`.
+    pub fn parent_count(&self) -> usize {
+        usize::from(self.list.parent_counts[self.index])
+    }
+
+    /// First line of the message.
+    pub fn summary(&self) -> &'a str {
+        self.list.summaries.get(self.index)
+    }
+
+    /// Graph row drawn before the row.
+    pub fn graph(&self) -> GraphRow {
+        let (column, lanes) = self.list.graph[self.index];
+        GraphRow {
+            column: column as usize,
+            lanes: lanes as usize,
+        }
+    }
+
+    /// Set for the pseudo-commit rows of uncommitted changes.
+    pub fn uncommitted(&self) -> Option<Uncommitted> {
+        match self.list.flags[self.index] {
+            f if f & STAGED != 0 => Some(Uncommitted::Staged),
+            f if f & UNSTAGED != 0 => Some(Uncommitted::Unstaged),
+            _ => None,
+        }
+    }
+
+    /// Name of the committer or the author, as recorded if `raw`.
+    pub fn name(&self, committer: bool, raw: bool) -> &'a str {
+        let person = self.person(committer);
+        let name = if raw { person.raw_name } else { person.name };
+        &self.list.names.names[name as usize]
+    }
+
+    /// Unix timestamp of the commit or of the authorship.
+    pub fn time(&self, committer: bool) -> i64 {
+        self.person(committer).time
+    }
+
+    /// Whether author and committer differ in identity or time.
+    pub fn rewritten(&self) -> bool {
+        self.list.flags[self.index] & REWRITTEN != 0
+    }
+
+    /// Number of `Co-authored-by` trailers.
+    pub fn co_author_count(&self) -> usize {
+        usize::from(self.list.co_authors[self.index])
+    }
+
+    /// The row as `abc1234 ("summary", YYYY-MM-DD)`; see
+    /// [`format_reference`].
+    pub fn reference(&self) -> String {
+        format_reference(self.id(), self.summary(), self.time(false))
+    }
+
+    fn person(&self, committer: bool) -> &'a Person {
+        if committer {
+            &self.list.committers[self.index]
+        } else {
+            &self.list.authors[self.index]
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use reki::git::{Diff, Oid, Repo, Uncommitted};
+
+use super::{App, View};
+use crate::format::{DetailLine, detail_lines};
+
+/// What a diff view compares.
+#[derive(Clone, Copy, Debug)]
+pub enum DiffSource {
+    /// Tree of `from` against tree of `to`.
+    Range { from: Oid, to: Oid },
+    /// Stash commit `id` (`stash@{index}`) against its base commit.
+    Stash { id: Oid, index: usize },
+    /// A commit against its first parent.
+    Commit { id: Oid },
+    /// Index against HEAD.
+    Staged,
+    /// Working tree against the index.
+    Unstaged,
+}
+
+impl DiffSource {
+    /// Compute the diff from the repository.
+    pub fn load(&self, repo: &Repo) -> Result<Diff> {
+        let diff = match *self {
+            DiffSource::Range { from, to } => repo.diff_commits(from, to),
+            DiffSource::Stash { id, .. } => repo.diff_stash(id),
+            DiffSource::Commit { id } => repo.diff_commit(id),
+            DiffSource::Staged => repo.diff_staged(),
+            DiffSource::Unstaged => repo.diff_unstaged(),
+        };
+        Ok(diff?)
+    }
+
+    /// Title for the view's border.
+    pub fn title(&self) -> String {
+        match self {
+            DiffSource::Range { from, to } => format!("{from:.7}..{to:.7}"),
+            DiffSource::Stash { index, .. } => format!("stash@{{{index}}}"),
+            DiffSource::Commit { id } => format!("{id:.7}"),
+            DiffSource::Staged => "Staged changes".to_string(),
+            DiffSource::Unstaged => "Unstaged changes".to_string(),
+        }
+    }
+
+    /// Commit header shown above the patch, for views of a single commit.
+    pub fn header(&self, repo: &Repo) -> Result<Vec<DetailLine>> {
+        match *self {
+            DiffSource::Commit { id } => Ok(detail_lines(&repo.commit_details(id)?)),
+            DiffSource::Range { .. }
+            | DiffSource::Stash { .. }
+            | DiffSource::Staged
+            | DiffSource::Unstaged => Ok(Vec::new()),
+        }
+    }
+
+    /// The same comparison in the opposite direction, if it has one.
+    fn swapped(&self) -> Option<Self> {
+        match *self {
+            DiffSource::Range { from, to } => Some(DiffSource::Range { from: to, to: from }),
+            DiffSource::Stash { .. }
+            | DiffSource::Commit { .. }
+            | DiffSource::Staged
+            | DiffSource::Unstaged => None,
+        }
+    }
+}
+
+/// Which pane of the diff view receives movement keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffFocus {
+    Files,
+    Patch,
+}
+
+/// State of a file list + patch view.
+pub struct DiffView {
+    pub source: DiffSource,
+    pub diff: Diff,
+    /// Commit details drawn above every file's patch (empty for ranges).
+    pub header: Vec<DetailLine>,
+    /// Selected index into `diff.files`.
+    pub selected_file: usize,
+    /// First visible line of the header and the selected file's patch.
+    pub scroll: usize,
+    pub focus: DiffFocus,
+}
+
+impl DiffView {
+    /// Load the diff and header for `source`.
+    pub fn load(source: DiffSource, repo: &Repo) -> Result<Self> {
+        Ok(Self {
+            source,
+            diff: source.load(repo)?,
+            header: source.header(repo)?,
+            selected_file: 0,
+            scroll: 0,
+            focus: DiffFocus::Files,
+        })
+    }
+
+    fn patch_len(&self) -> usize {
+        self.header.len()
+            + self
+                .diff
+                .files
+                .get(self.selected_file)
+                .map_or(0, |f| f.lines.len())
+    }
+
+    fn move_down(&mut self, n: usize) {
+        match self.focus {
+            DiffFocus::Files => {
+                let max = self.diff.files.len().saturating_sub(1);
+                self.selected_file = (self.selected_file + n).min(max);
+                self.scroll = 0;
+            }
+            DiffFocus::Patch => {
+                let max = self.patch_len().saturating_sub(1);
+                self.scroll = (self.scroll + n).min(max);
+            }
+        }
+    }
+
+    fn move_up(&mut self, n: usize) {
+        match self.focus {
+            DiffFocus::Files => {
+                self.selected_file = self.selected_file.saturating_sub(n);
+                self.scroll = 0;
+            }
+            DiffFocus::Patch => self.scroll = self.scroll.saturating_sub(n),
+        }
+    }
+}
+
+impl App {
+    /// Open a diff view for `source` on top of the current view.
+    pub(super) fn open_diff(&mut self, source: DiffSource) {
+        match DiffView::load(source, &self.repo) {
+            Ok(view) => self.views.push(View::Diff(view)),
+            Err(e) => self.error(format!("diff failed: {e}")),
+        }
+    }
+
+    /// Open the changes of the selected row: a commit against its parent,
+    /// or the staged / unstaged changes of a pseudo-commit row.
+    pub(super) fn open_selected_diff(&mut self) {
+        let Some(c) = self.commits.get(self.selected) else {
+            return;
+        };
+        let source = match c.uncommitted() {
+            Some(Uncommitted::Staged) => DiffSource::Staged,
+            Some(Uncommitted::Unstaged) => DiffSource::Unstaged,
+            None => DiffSource::Commit { id: c.id() },
+        };
+        self.open_diff(source);
+    }
+
+    /// Diff the marked commit against the selected one.
+    pub(super) fn diff_marked(&mut self) {
+        let Some(from) = self.marked else {
+            self.info("Mark a commit with m first");
+            return;
+        };
+        let Some(to) = self.selected_commit().map(|c| c.id()) else {
+            return;
+        };
+        if from == to {
+            self.info("Select a different commit to diff against the mark");
+            return;
+        }
+        self.open_diff(DiffSource::Range { from, to });
+    }
+
+    /// Open the log of the submodule selected in the diff view, at the
+    /// commit the diff moves it to. Closing that log comes back here.
+    fn open_submodule(&mut self) {
+        let Some(View::Diff(view)) = self.views.last() else {
+            return;
+        };
+        let Some(file) = view.diff.files.get(view.selected_file) else {
+            return;
+        };
+        let Some(change) = &file.submodule else {
+            return;
+        };
+        let (path, target) = (file.path.clone(), change.new.or(change.old));
+        let Some(dir) = self.repo.submodule_path(&path) else {
+            self.error(format!("Submodule {path} is not checked out"));
+            return;
+        };
+        let mut repo = match Repo::open_with(&dir, self.repo.backend()) {
+            Ok(repo) => repo,
+            Err(e) => {
+                self.error(format!("{path}: {e}"));
+                return;
+            }
+        };
+        repo.set_order(self.repo.order());
+        // The recorded commit need not be on the checked-out branch.
+        let target = target.filter(|id| repo.resolve(&id.to_string()).is_ok());
+        repo.set_extra_tip(target);
+        let mut app = match App::new(repo) {
+            Ok(app) => app,
+            Err(e) => {
+                self.error(format!("{path}: {e}"));
+                return;
+            }
+        };
+        app.page_height = self.page_height;
+        app.last_yank = self.last_yank.take();
+        app.submodule = Some(path);
+        if let Some(id) = target {
+            app.select_oid(id);
+        }
+        let parent = std::mem::replace(self, app);
+        self.parent = Some(Box::new(parent));
+    }
+
+    /// Handle a key event in the diff view.
+    pub(super) fn handle_diff_key(&mut self, event: KeyEvent) {
+        let page = self.page_height;
+        let Some(View::Diff(view)) = self.views.last_mut() else {
+            return;
+        };
+        match event.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.views.pop();
+            }
+            KeyCode::Tab => {
+                view.focus = match view.focus {
+                    DiffFocus::Files => DiffFocus::Patch,
+                    DiffFocus::Patch => DiffFocus::Files,
+                };
+            }
+            KeyCode::Char('j') | KeyCode::Down => view.move_down(1),
+            KeyCode::Char('k') | KeyCode::Up => view.move_up(1),
+            KeyCode::Enter => self.open_submodule(),
+            KeyCode::Char(' ') | KeyCode::PageDown => {
+                view.focus = DiffFocus::Patch;
+                view.move_down(page);
+            }
+            KeyCode::Char('-') | KeyCode::PageUp => {
+                view.focus = DiffFocus::Patch;
+                view.move_up(page);
+            }
+            KeyCode::Char('s') => {
+                let Some(source) = view.source.swapped() else {
+                    return;
+                };
+                match DiffView::load(source, &self.repo) {
+                    Ok(swapped) => {
+                        let selected = view.selected_file;
+                        *view = swapped;
+                        view.selected_file = selected.min(view.diff.files.len().saturating_sub(1));
+                    }
+                    Err(e) => self.error(format!("diff failed: {e}")),
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,666 @@
+mod action;
+mod commits;
+mod diff;
+mod rebase;
+mod reflog;
+mod stash;
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use reki::git::{CommitSource, Graph, Oid, RefDecoration, Repo, SignatureStatus, TrailerFilter};
+
+use crate::export::{ExportFormat, export};
+use crate::prompt::{Prompt, PromptKind};
+use crate::watcher::RepoWatcher;
+
+pub use action::Action;
+pub use commits::{CommitList, Row};
+pub use diff::{DiffFocus, DiffView};
+pub use rebase::RebaseView;
+pub use reflog::ReflogView;
+pub use stash::StashView;
+
+/// Signatures verified per tick; each one may spawn `gpg` or `ssh-keygen`,
+/// so checking a whole page at once would stall input.
+const SIGNATURE_CHECKS_PER_TICK: usize = 2;
+
+/// A one-shot message for the status bar.
+pub struct Status {
+    pub text: String,
+    /// Errors are highlighted so failed operations stand out.
+    pub is_error: bool,
+}
+
+/// A screen drawn on top of the log.
+pub enum View {
+    Diff(DiffView),
+    Rebase(RebaseView),
+    Stash(StashView),
+    Reflog(ReflogView),
+}
+
+/// App state management, over a [`Repo`] or, in tests, another
+/// [`CommitSource`].
+pub struct App<S = Repo> {
+    /// Whether the application should quit.
+    pub should_quit: bool,
+    /// Git repository handle.
+    repo: S,
+    /// Loaded commits, with their rendered graph lines.
+    pub commits: CommitList,
+    /// Lane-tracking state for the ASCII graph.
+    graph: Graph,
+    /// Whether all commits have been loaded.
+    pub all_loaded: bool,
+    /// Currently selected commit index.
+    pub selected: usize,
+    /// Visible rows in the log viewport (set by UI on each draw).
+    pub page_height: usize,
+    /// Horizontal scroll offset in display columns.
+    pub scroll_x: usize,
+    /// Upper bound for scroll_x (set by UI each frame).
+    pub max_scroll_x: usize,
+    /// Active input prompt, if any (takes all key input while open).
+    pub prompt: Option<Prompt>,
+    /// One-shot message for the status bar, cleared on the next key press.
+    pub status: Option<Status>,
+    /// First key of a two-key command (e.g. `y` for yank), awaiting the second.
+    pub pending_prefix: Option<char>,
+    /// Text waiting to be sent to the terminal clipboard (taken by the main loop).
+    pub clipboard: Option<String>,
+    /// Most recently yanked text, kept for the `--yank-to` fallback on exit.
+    pub last_yank: Option<String>,
+    /// Commit marked as the base of a two-commit diff.
+    pub marked: Option<Oid>,
+    /// Views opened on top of the log; the last one is shown and gets input.
+    pub views: Vec<View>,
+    /// Show author names as recorded rather than mapped through `.mailmap`.
+    pub raw_identities: bool,
+    /// Show committer name and date in the log instead of the author's.
+    pub show_committer: bool,
+    /// Verified signature status per commit, filled in around the selection
+    /// by [`App::tick`].
+    pub signatures: HashMap<Oid, SignatureStatus>,
+    /// Detects ref and index changes made outside reki (only for a [`Repo`]).
+    watcher: Option<RepoWatcher>,
+    /// Path of the submodule shown, when this log was opened from a diff
+    /// of its parent repository.
+    pub submodule: Option<String>,
+    /// The parent repository's app, restored when this log is closed.
+    parent: Option<Box<Self>>,
+}
+
+impl App {
+    /// Create a new App showing the log of `repo`, reloading it when the
+    /// repository changes on disk.
+    pub fn new(repo: Repo) -> Result<Self> {
+        let (git_dir, common_dir) = repo.git_dirs();
+        let watcher = RepoWatcher::new(git_dir, common_dir);
+        let mut app = Self::with_source(repo)?;
+        app.watcher = Some(watcher);
+        Ok(app)
+    }
+
+    /// Periodic work between key events: reload when the repository
+    /// changed on disk (commit, fetch, rebase in another terminal).
+    pub fn tick(&mut self) {
+        if self.watcher.as_mut().is_some_and(RepoWatcher::poll) {
+            self.reload();
+        }
+        self.verify_signatures();
+    }
+
+    /// Verify a few not yet checked signatures of rows within a page of the
+    /// selection.
+    fn verify_signatures(&mut self) {
+        let start = self.selected.saturating_sub(self.page_height);
+        let end = self.selected + self.page_height;
+        let pending: Vec<Oid> = self
+            .commits
+            .range(start, end)
+            .filter(|c| c.uncommitted().is_none() && !self.signatures.contains_key(&c.id()))
+            .map(|c| c.id())
+            .take(SIGNATURE_CHECKS_PER_TICK)
+            .collect();
+        for id in pending {
+            let status = self.repo.verify_signature(id).status;
+            self.signatures.insert(id, status);
+        }
+    }
+
+    /// Handle a key event.
+    pub fn handle_event(&mut self, event: KeyEvent) {
+        if self.prompt.is_some() {
+            self.handle_prompt_key(event);
+            return;
+        }
+        self.status = None;
+
+        if let Some(prefix) = self.pending_prefix.take() {
+            self.handle_prefixed_key(prefix, event);
+            return;
+        }
+
+        match self.views.last() {
+            Some(View::Diff(_)) => self.handle_diff_key(event),
+            Some(View::Rebase(_)) => self.handle_rebase_key(event),
+            Some(View::Stash(_)) => self.handle_stash_key(event),
+            Some(View::Reflog(_)) => self.handle_reflog_key(event),
+            None => self.handle_log_key(event),
+        }
+    }
+
+    /// Handle a key event in the log view.
+    fn handle_log_key(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.close_log(),
+
+            // Single-line movement.
+            KeyCode::Char('j') | KeyCode::Down => self.move_down(1),
+            KeyCode::Char('k') | KeyCode::Up => self.move_up(1),
+
+            // Page movement.
+            KeyCode::Char(' ') | KeyCode::PageDown => self.move_down(self.page_height),
+            KeyCode::Char('-') | KeyCode::Char('a') | KeyCode::PageUp => {
+                self.move_up(self.page_height);
+            }
+
+            // Horizontal scroll.
+            KeyCode::Char('h') | KeyCode::Left => self.scroll_left(2),
+            KeyCode::Char('l') | KeyCode::Right => self.scroll_right(2),
+
+            // Jump to top / bottom.
+            KeyCode::Char('g') | KeyCode::Home => self.selected = 0,
+            KeyCode::Char('G') | KeyCode::End => self.jump_to_end(),
+
+            // Jump to a revspec.
+            KeyCode::Char(':') => self.prompt = Some(Prompt::new(PromptKind::Goto)),
+
+            // Yank prefix; the next key picks what to copy.
+            KeyCode::Char('y') => {
+                self.pending_prefix = Some('y');
+                self.info("yank: [y] hash  [h] short hash  [s] summary  [r] reference");
+            }
+
+            // Changes of the selected commit or uncommitted row.
+            KeyCode::Enter => self.open_selected_diff(),
+
+            // Mark a commit, then diff it against the selection.
+            KeyCode::Char('m') => self.toggle_mark(),
+            KeyCode::Char('d') => self.diff_marked(),
+
+            // Branch and tag edits.
+            KeyCode::Char(key @ ('b' | 't' | 'T' | 'R' | 'D')) => self.start_ref_edit(key),
+
+            // Checkout.
+            KeyCode::Char(key @ ('c' | 'C')) => self.start_checkout(key),
+
+            // Cherry-pick / revert onto HEAD.
+            KeyCode::Char(key @ ('p' | 'v')) => self.start_apply(key),
+
+            // Interactive rebase of the selected commit up to HEAD.
+            KeyCode::Char('r') => self.open_rebase_planner(),
+
+            // Stash list.
+            KeyCode::Char('S') => self.open_stash_list(),
+
+            // Reflog of HEAD or another ref.
+            KeyCode::Char('L') => self.start_reflog(),
+
+            // Filter by trailer.
+            KeyCode::Char('f') => {
+                let input = self
+                    .repo
+                    .filter()
+                    .map_or_else(String::new, ToString::to_string);
+                self.prompt = Some(Prompt::with_input(PromptKind::TrailerFilter, input));
+            }
+
+            // Author names as recorded instead of mapped through .mailmap.
+            KeyCode::Char('M') => {
+                self.raw_identities = !self.raw_identities;
+                self.info(if self.raw_identities {
+                    "Showing raw author names"
+                } else {
+                    "Showing .mailmap author names"
+                });
+            }
+
+            // Export the log to a file.
+            KeyCode::Char('E') => self.prompt = Some(Prompt::new(PromptKind::Export)),
+
+            // Author or committer name and date.
+            KeyCode::Char('A') => {
+                self.show_committer = !self.show_committer;
+                self.info(if self.show_committer {
+                    "Showing committer name and date"
+                } else {
+                    "Showing author name and date"
+                });
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Quit, or go back from a submodule's log to the parent repository.
+    fn close_log(&mut self) {
+        let Some(parent) = self.parent.take() else {
+            self.should_quit = true;
+            return;
+        };
+        let child = std::mem::replace(self, *parent);
+        self.page_height = child.page_height;
+        self.last_yank = child.last_yank;
+    }
+
+    /// Handle the second key of a two-key command.
+    fn handle_prefixed_key(&mut self, prefix: char, event: KeyEvent) {
+        let KeyCode::Char(ch) = event.code else {
+            return;
+        };
+        if prefix == 'y' {
+            self.yank(ch);
+        }
+    }
+
+    /// The selected row, unless it is a pseudo-commit of uncommitted changes.
+    fn selected_commit(&self) -> Option<Row<'_>> {
+        self.commits
+            .get(self.selected)
+            .filter(|c| c.uncommitted().is_none())
+    }
+
+    /// Mark the selected commit, or clear the mark if it is already marked.
+    fn toggle_mark(&mut self) {
+        let Some(id) = self.selected_commit().map(|c| c.id()) else {
+            return;
+        };
+        if self.marked == Some(id) {
+            self.marked = None;
+        } else {
+            self.marked = Some(id);
+            self.info(format!("Marked {id:.7}; select another commit and press d"));
+        }
+    }
+
+    /// Copy a field of the selected commit to the clipboard.
+    fn yank(&mut self, what: char) {
+        let Some(c) = self.selected_commit() else {
+            return;
+        };
+        let (text, label) = match what {
+            'y' => (c.id().to_string(), "hash"),
+            'h' => (format!("{:.7}", c.id()), "short hash"),
+            's' => (c.summary().to_string(), "summary"),
+            'r' => (c.reference(), "reference"),
+            _ => return,
+        };
+        self.info(format!("Copied {label}: {text}"));
+        self.clipboard = Some(text.clone());
+        self.last_yank = Some(text);
+    }
+
+    /// Handle a key event while a prompt is open.
+    fn handle_prompt_key(&mut self, event: KeyEvent) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        if matches!(prompt.kind, PromptKind::Confirm(_)) {
+            if let Some(Prompt {
+                kind: PromptKind::Confirm(action),
+                ..
+            }) = self.prompt.take()
+            {
+                if matches!(event.code, KeyCode::Char('y' | 'Y')) {
+                    self.run_action(action);
+                } else {
+                    self.decline(action);
+                }
+            }
+            return;
+        }
+        match event.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => {
+                if let Some(prompt) = self.prompt.take() {
+                    self.submit_prompt(prompt);
+                }
+            }
+            KeyCode::Backspace => prompt.backspace(),
+            KeyCode::Tab => {
+                let names = match prompt.kind {
+                    PromptKind::RenameBranch
+                    | PromptKind::DeleteBranch
+                    | PromptKind::CheckoutBranch => self.repo.local_branches(),
+                    _ => self.repo.ref_names(),
+                };
+                prompt.complete(&names);
+            }
+            KeyCode::Char('u') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                prompt.clear();
+            }
+            KeyCode::Char(ch) => prompt.push(ch),
+            _ => {}
+        }
+    }
+
+    /// Act on the text entered in a prompt.
+    fn submit_prompt(&mut self, prompt: Prompt) {
+        let input = prompt.input.trim().to_string();
+        if let PromptKind::TrailerFilter = prompt.kind {
+            self.set_trailer_filter(TrailerFilter::parse(&input));
+            return;
+        }
+        if input.is_empty() {
+            return;
+        }
+        match prompt.kind {
+            PromptKind::Goto => self.goto(&input),
+            PromptKind::CreateBranch { target } => self.confirm(Action::CreateBranch {
+                name: input,
+                target,
+            }),
+            PromptKind::CreateTag {
+                target,
+                annotated: false,
+            } => self.confirm(Action::CreateTag {
+                name: input,
+                target,
+                message: None,
+            }),
+            PromptKind::CreateTag {
+                target,
+                annotated: true,
+            } => {
+                self.prompt = Some(Prompt::new(PromptKind::TagMessage {
+                    target,
+                    name: input,
+                }));
+            }
+            PromptKind::TagMessage { target, name } => self.confirm(Action::CreateTag {
+                name,
+                target,
+                message: Some(input),
+            }),
+            PromptKind::RenameBranch => {
+                self.prompt = Some(Prompt::with_input(
+                    PromptKind::RenameBranchTo {
+                        from: input.clone(),
+                    },
+                    input,
+                ));
+            }
+            PromptKind::RenameBranchTo { from } => {
+                if from != input {
+                    self.confirm(Action::RenameBranch { from, to: input });
+                }
+            }
+            PromptKind::DeleteBranch => self.confirm(Action::DeleteBranch { name: input }),
+            PromptKind::CheckoutBranch => self.confirm(Action::CheckoutBranch { name: input }),
+            PromptKind::Mainline {
+                target,
+                revert,
+                parents,
+            } => match input.parse::<u32>() {
+                Ok(n) if n >= 1 && n as usize <= parents => {
+                    self.confirm(if revert {
+                        Action::Revert {
+                            target,
+                            mainline: n,
+                        }
+                    } else {
+                        Action::CherryPick {
+                            target,
+                            mainline: n,
+                        }
+                    });
+                }
+                _ => self.error(format!("mainline must be a number from 1 to {parents}")),
+            },
+            PromptKind::Reword { index } => self.reword(index, input),
+            PromptKind::Reflog => self.open_reflog(&input),
+            PromptKind::Export => self.export(&input),
+            PromptKind::TrailerFilter | PromptKind::Confirm(_) => {}
+        }
+    }
+
+    /// Show only commits matching `filter`, or the whole log for `None`.
+    fn set_trailer_filter(&mut self, filter: Option<TrailerFilter>) {
+        if self.repo.filter() == filter.as_ref() {
+            return;
+        }
+        self.repo.set_filter(filter);
+        match self.repo.filter() {
+            // The selected commit may not match, and looking for it would
+            // walk the whole history: start from the top instead.
+            Some(f) => {
+                let f = f.to_string();
+                if self.restart_log() && self.commits.is_empty() {
+                    self.info(format!("No commits with {f}"));
+                }
+            }
+            None => {
+                self.reload();
+                self.info("Filter cleared");
+            }
+        }
+    }
+
+    /// Write the whole log (range and filter applied) to `path`.
+    fn export(&mut self, path: &str) {
+        let path = std::path::Path::new(path);
+        let Some(format) = ExportFormat::from_path(path) else {
+            self.error("Export file must end in .json, .ndjson, .jsonl or .csv");
+            return;
+        };
+        let result = std::fs::File::create(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| {
+                export(
+                    &self.repo,
+                    format,
+                    false,
+                    None,
+                    &mut std::io::BufWriter::new(file),
+                )
+            });
+        match result {
+            Ok(n) => self.info(format!("Exported {n} commits to {}", path.display())),
+            Err(e) => self.error(format!("export failed: {e}")),
+        }
+    }
+
+    /// Select the commit named by `spec`, loading batches until it is found.
+    fn goto(&mut self, spec: &str) {
+        let oid = match self.repo.resolve(spec) {
+            Ok(oid) => oid,
+            Err(e) => {
+                self.error(format!("{spec}: {e}"));
+                return;
+            }
+        };
+        if !self.select_oid(oid) {
+            self.error(format!(
+                "{spec} ({oid:.7}) is not reachable from the current log"
+            ));
+        }
+    }
+}
+
+/// Loading and navigation, which only need a [`CommitSource`].
+impl<S: CommitSource> App<S> {
+    /// Create an App showing the log of `source`.
+    pub fn with_source(source: S) -> Result<Self> {
+        let mut app = Self {
+            should_quit: false,
+            repo: source,
+            commits: CommitList::default(),
+            graph: Graph::new(),
+            all_loaded: false,
+            selected: 0,
+            page_height: 20,
+            scroll_x: 0,
+            max_scroll_x: 0,
+            prompt: None,
+            status: None,
+            pending_prefix: None,
+            clipboard: None,
+            last_yank: None,
+            marked: None,
+            views: Vec::new(),
+            raw_identities: false,
+            show_committer: false,
+            signatures: HashMap::new(),
+            watcher: None,
+            submodule: None,
+            parent: None,
+        };
+        app.load_more_commits()?;
+        Ok(app)
+    }
+
+    /// Load the next batch of commits.
+    pub fn load_more_commits(&mut self) -> Result<()> {
+        if self.all_loaded {
+            return Ok(());
+        }
+        let mut batch = self.repo.load_commits()?;
+        let filtered = self.repo.filter().is_some();
+        if self.commits.is_empty() && !filtered && self.repo.range().is_none() {
+            // Uncommitted changes sit above HEAD as pseudo-commits.
+            batch.splice(0..0, self.repo.uncommitted_rows());
+        }
+        if batch.is_empty() {
+            self.all_loaded = true;
+        } else {
+            for c in &batch {
+                // Parents of filtered commits are mostly hidden, so the
+                // filtered log is drawn as a flat list.
+                let parents = if filtered { &[][..] } else { &c.parent_ids };
+                let row = self.graph.next_node(c.id, parents);
+                self.commits.push(c, row);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reload refs and history from scratch, keeping the selection on the
+    /// same commit if it is still part of the log.
+    pub fn reload(&mut self) {
+        let selected_id = self.commits.get(self.selected).map(|c| c.id());
+        if !self.restart_log() {
+            return;
+        }
+        if let Some(id) = selected_id
+            && !self.select_oid(id)
+        {
+            self.selected = 0;
+        }
+    }
+
+    /// Re-read refs and load the log again from the top. Returns false
+    /// (after reporting the error) if that failed.
+    fn restart_log(&mut self) -> bool {
+        if let Err(e) = self.repo.reload() {
+            self.error(format!("reload failed: {e}"));
+            return false;
+        }
+        self.commits.clear();
+        self.graph = Graph::new();
+        self.all_loaded = false;
+        self.selected = 0;
+        if let Err(e) = self.load_more_commits() {
+            self.error(format!("reload failed: {e}"));
+            return false;
+        }
+        true
+    }
+
+    /// Show an informational message in the status bar.
+    pub(crate) fn info(&mut self, text: impl Into<String>) {
+        self.status = Some(Status {
+            text: text.into(),
+            is_error: false,
+        });
+    }
+
+    /// Show an error in the status bar.
+    pub(crate) fn error(&mut self, text: impl Into<String>) {
+        self.status = Some(Status {
+            text: text.into(),
+            is_error: true,
+        });
+    }
+
+    /// The revision range shown instead of HEAD, if any.
+    pub fn range(&self) -> Option<&str> {
+        self.repo.range()
+    }
+
+    /// The active trailer filter of the log, if any.
+    pub fn trailer_filter(&self) -> Option<&TrailerFilter> {
+        self.repo.filter()
+    }
+
+    /// Reference decorations pointing at `oid`.
+    pub fn refs_for(&self, oid: Oid) -> &[RefDecoration] {
+        self.repo.refs_for(oid)
+    }
+
+    /// Move the selection to the commit `oid`, loading more commits as needed.
+    /// Returns `false` if the commit is not part of the walk.
+    fn select_oid(&mut self, oid: Oid) -> bool {
+        let mut searched = 0;
+        loop {
+            if let Some(pos) = self.commits.position(oid, searched) {
+                self.selected = pos;
+                self.maybe_load_more();
+                return true;
+            }
+            searched = self.commits.len();
+            if self.all_loaded || self.load_more_commits().is_err() {
+                return false;
+            }
+        }
+    }
+
+    fn move_down(&mut self, n: usize) {
+        let max = self.commits.len().saturating_sub(1);
+        self.selected = (self.selected + n).min(max);
+        self.maybe_load_more();
+    }
+
+    fn move_up(&mut self, n: usize) {
+        self.selected = self.selected.saturating_sub(n);
+    }
+
+    fn scroll_right(&mut self, n: usize) {
+        self.scroll_x = (self.scroll_x + n).min(self.max_scroll_x);
+    }
+
+    fn scroll_left(&mut self, n: usize) {
+        self.scroll_x = self.scroll_x.saturating_sub(n);
+    }
+
+    /// When the cursor is within one page of the end, load more commits.
+    fn maybe_load_more(&mut self) {
+        if !self.all_loaded && self.selected + self.page_height >= self.commits.len() {
+            let _ = self.load_more_commits();
+        }
+    }
+
+    /// Jump to the very last commit, loading all remaining if needed.
+    fn jump_to_end(&mut self) {
+        while !self.all_loaded {
+            if self.load_more_commits().is_err() {
+                break;
+            }
+        }
+        self.selected = self.commits.len().saturating_sub(1);
+    }
+}
@@ -0,0 +1,228 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use reki::git::{Oid, RebasePlan, RebaseRun, RebaseStatus, TodoAction, TodoEntry};
+
+use super::{Action, App, View};
+use crate::format::list_paths;
+use crate::prompt::{Prompt, PromptKind};
+
+/// State of the interactive rebase planner.
+pub struct RebaseView {
+    /// Commit the planned commits are replayed onto.
+    pub onto: Oid,
+    /// Todo list, oldest commit first.
+    pub todo: Vec<TodoEntry>,
+    /// Selected index into `todo`.
+    pub selected: usize,
+    /// The rebase once started; `Some` means it is paused on conflicts.
+    pub run: Option<RebaseRun>,
+    /// Conflicted paths of the paused step.
+    pub conflicts: Vec<String>,
+}
+
+impl RebaseView {
+    fn set_action(&mut self, action: TodoAction) {
+        if let Some(entry) = self.todo.get_mut(self.selected) {
+            entry.action = action;
+        }
+    }
+
+    /// Move the selected entry one place up (`-1`) or down (`1`).
+    fn shift(&mut self, delta: isize) {
+        let Some(to) = self.selected.checked_add_signed(delta) else {
+            return;
+        };
+        if to < self.todo.len() {
+            self.todo.swap(self.selected, to);
+            self.selected = to;
+        }
+    }
+}
+
+impl App {
+    /// Open the planner for the commits from the selected one up to HEAD,
+    /// or the paused rebase if there is one.
+    pub(super) fn open_rebase_planner(&mut self) {
+        match self.repo.rebase_in_progress() {
+            Ok(Some(run)) => {
+                let conflicts = self.repo.conflicted_paths().unwrap_or_default();
+                self.views.push(View::Rebase(RebaseView {
+                    onto: run.onto,
+                    todo: run.todo.clone(),
+                    selected: run.next,
+                    run: Some(run),
+                    conflicts,
+                }));
+                self.info("Rebase in progress: c to continue, A to abort");
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                self.error(format!("rebase: {e}"));
+                return;
+            }
+        }
+        let Some(base) = self.selected_commit().map(|c| c.id()) else {
+            return;
+        };
+        match self.repo.plan_rebase(base) {
+            Ok(RebasePlan { onto, todo }) => self.views.push(View::Rebase(RebaseView {
+                onto,
+                todo,
+                selected: 0,
+                run: None,
+                conflicts: Vec::new(),
+            })),
+            Err(e) => self.error(format!("rebase: {e}")),
+        }
+    }
+
+    /// Start the planned rebase (after confirmation).
+    pub(super) fn start_rebase(&mut self) {
+        let Some(View::Rebase(view)) = self.views.last_mut() else {
+            return;
+        };
+        let plan = RebasePlan {
+            onto: view.onto,
+            todo: view.todo.clone(),
+        };
+        match self.repo.start_rebase(plan) {
+            Ok((run, status)) => self.rebase_stopped(run, status),
+            Err(e) => self.error(format!("rebase failed: {e}")),
+        }
+    }
+
+    /// Abort the paused rebase and restore the original HEAD.
+    pub(super) fn abort_rebase(&mut self) {
+        let Some(View::Rebase(view)) = self.views.last_mut() else {
+            return;
+        };
+        let Some(run) = view.run.take() else {
+            return;
+        };
+        view.conflicts.clear();
+        match self.repo.abort_rebase(&run) {
+            Ok(()) => {
+                self.views.pop();
+                self.reload();
+                self.info("Rebase aborted");
+            }
+            Err(e) => {
+                self.error(format!("abort failed: {e}"));
+                if let Some(View::Rebase(view)) = self.views.last_mut() {
+                    view.run = Some(run);
+                }
+            }
+        }
+    }
+
+    /// Continue the paused rebase once conflicts are resolved and staged.
+    fn continue_rebase(&mut self) {
+        let Some(View::Rebase(view)) = self.views.last_mut() else {
+            return;
+        };
+        let Some(mut run) = view.run.take() else {
+            return;
+        };
+        match self.repo.continue_rebase(&mut run) {
+            Ok(status) => self.rebase_stopped(run, status),
+            Err(e) => {
+                self.error(format!("continue failed: {e}"));
+                if let Some(View::Rebase(view)) = self.views.last_mut() {
+                    view.run = Some(run);
+                }
+            }
+        }
+    }
+
+    /// Update the planner after a rebase finished or paused.
+    fn rebase_stopped(&mut self, run: RebaseRun, status: RebaseStatus) {
+        match status {
+            RebaseStatus::Done(head) => {
+                self.views.pop();
+                self.reload();
+                self.select_oid(head);
+                self.info(format!("Rebased onto {:.7}", run.onto));
+            }
+            RebaseStatus::Conflicts(paths) => {
+                self.error(format!(
+                    "Stopped at {} {:.7}: conflicts in {}; resolve, stage, then c to continue or A to abort",
+                    run.todo[run.next].action.name(),
+                    run.todo[run.next].id,
+                    list_paths(&paths)
+                ));
+                if let Some(View::Rebase(view)) = self.views.last_mut() {
+                    view.selected = run.next;
+                    view.todo.clone_from(&run.todo);
+                    view.conflicts = paths;
+                    view.run = Some(run);
+                }
+            }
+        }
+    }
+
+    /// Handle a key event in the rebase planner.
+    pub(super) fn handle_rebase_key(&mut self, event: KeyEvent) {
+        let Some(View::Rebase(view)) = self.views.last_mut() else {
+            return;
+        };
+
+        // While paused the plan is fixed. Leaving keeps the rebase paused on
+        // disk; `r` in the log comes back to it.
+        if view.run.is_some() {
+            match event.code {
+                KeyCode::Char('c') => self.continue_rebase(),
+                KeyCode::Char('A') => self.confirm(Action::AbortRebase),
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    self.views.pop();
+                    self.reload();
+                    self.info("Rebase paused: r returns to it");
+                }
+                _ => self.info("Rebase in progress: c to continue, A to abort, q to leave"),
+            }
+            return;
+        }
+
+        match event.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.views.pop();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                view.selected = (view.selected + 1).min(view.todo.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => view.selected = view.selected.saturating_sub(1),
+            KeyCode::Char('J') => view.shift(1),
+            KeyCode::Char('K') => view.shift(-1),
+            KeyCode::Char('p') => view.set_action(TodoAction::Pick),
+            KeyCode::Char('s') => view.set_action(TodoAction::Squash),
+            KeyCode::Char('f') => view.set_action(TodoAction::Fixup),
+            KeyCode::Char('d') => view.set_action(TodoAction::Drop),
+            KeyCode::Char('r') => {
+                if let Some(entry) = view.todo.get(view.selected) {
+                    let index = view.selected;
+                    let summary = entry.summary.clone();
+                    self.prompt = Some(Prompt::with_input(PromptKind::Reword { index }, summary));
+                }
+            }
+            KeyCode::Enter => {
+                let n = view.todo.len();
+                self.confirm(Action::Rebase { commits: n });
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply a reworded summary to the todo entry at `index`.
+    pub(super) fn reword(&mut self, index: usize, summary: String) {
+        let Some(View::Rebase(view)) = self.views.last_mut() else {
+            return;
+        };
+        let Some(entry) = view.todo.get_mut(index) else {
+            return;
+        };
+        // Keep the body; replace only the first line.
+        let body = entry.message.split_once('\n').map_or("", |(_, rest)| rest);
+        entry.message = format!("{summary}\n{body}");
+        entry.summary = summary;
+        entry.action = TodoAction::Reword;
+    }
+}
@@ -0,0 +1,75 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use reki::git::{Oid, ReflogEntry};
+
+use super::{App, View};
+use crate::prompt::{Prompt, PromptKind};
+
+/// State of the reflog of one reference.
+pub struct ReflogView {
+    /// Full name of the reference (e.g. `HEAD`, `refs/heads/main`).
+    pub name: String,
+    /// Entries, newest first.
+    pub entries: Vec<ReflogEntry>,
+    /// Selected index into `entries`.
+    pub selected: usize,
+}
+
+impl App {
+    /// Ask which reference's reflog to show, defaulting to HEAD.
+    pub(super) fn start_reflog(&mut self) {
+        self.prompt = Some(Prompt::with_input(PromptKind::Reflog, "HEAD".to_string()));
+    }
+
+    /// Open the reflog of `name` on top of the current view.
+    pub(super) fn open_reflog(&mut self, name: &str) {
+        match self.repo.reflog(name) {
+            Ok((name, entries)) => self.views.push(View::Reflog(ReflogView {
+                name,
+                entries,
+                selected: 0,
+            })),
+            Err(e) => self.error(format!("reflog for {name}: {e}")),
+        }
+    }
+
+    /// Show `oid` in the log, adding it to the walk if HEAD cannot reach
+    /// it. Only the latest such commit is kept in the log.
+    fn open_in_log(&mut self, oid: Oid) {
+        let tip = (!self.repo.reachable_from_head(oid)).then_some(oid);
+        if self.repo.set_extra_tip(tip) {
+            self.reload();
+        }
+        self.views.clear();
+        if !self.select_oid(oid) {
+            self.error(format!("{oid:.7} could not be found"));
+        }
+    }
+
+    /// Handle a key event in the reflog view.
+    pub(super) fn handle_reflog_key(&mut self, event: KeyEvent) {
+        let page = self.page_height;
+        let Some(View::Reflog(view)) = self.views.last_mut() else {
+            return;
+        };
+        let max = view.entries.len().saturating_sub(1);
+        match event.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.views.pop();
+            }
+            KeyCode::Char('j') | KeyCode::Down => view.selected = (view.selected + 1).min(max),
+            KeyCode::Char('k') | KeyCode::Up => view.selected = view.selected.saturating_sub(1),
+            KeyCode::Char(' ') | KeyCode::PageDown => {
+                view.selected = (view.selected + page).min(max);
+            }
+            KeyCode::Char('-') | KeyCode::PageUp => {
+                view.selected = view.selected.saturating_sub(page);
+            }
+            KeyCode::Enter => {
+                if let Some(oid) = view.entries.get(view.selected).map(|e| e.new_id) {
+                    self.open_in_log(oid);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use reki::git::StashEntry;
+
+use super::diff::DiffSource;
+use super::{Action, App, View};
+
+/// State of the stash list.
+pub struct StashView {
+    pub entries: Vec<StashEntry>,
+    /// Selected index into `entries`.
+    pub selected: usize,
+}
+
+impl App {
+    /// Open the stash list on top of the current view.
+    pub(super) fn open_stash_list(&mut self) {
+        match self.repo.stashes() {
+            Ok(entries) => self.views.push(View::Stash(StashView {
+                entries,
+                selected: 0,
+            })),
+            Err(e) => self.error(format!("stash list failed: {e}")),
+        }
+    }
+
+    /// Re-read the stash list after an entry was applied, popped or dropped.
+    pub(super) fn refresh_stash_list(&mut self) {
+        let entries = match self.repo.stashes() {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.error(format!("stash list failed: {e}"));
+                return;
+            }
+        };
+        if let Some(View::Stash(view)) = self.views.last_mut() {
+            view.selected = view.selected.min(entries.len().saturating_sub(1));
+            view.entries = entries;
+        }
+    }
+
+    /// Handle a key event in the stash list.
+    pub(super) fn handle_stash_key(&mut self, event: KeyEvent) {
+        let Some(View::Stash(view)) = self.views.last_mut() else {
+            return;
+        };
+        match event.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.views.pop();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                view.selected = (view.selected + 1).min(view.entries.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => view.selected = view.selected.saturating_sub(1),
+            code => {
+                let Some(entry) = view.entries.get(view.selected) else {
+                    return;
+                };
+                let (id, index) = (entry.id, entry.index);
+                match code {
+                    KeyCode::Enter => self.open_diff(DiffSource::Stash { id, index }),
+                    KeyCode::Char('a') => self.confirm(Action::StashApply { index }),
+                    KeyCode::Char('p') => self.confirm(Action::StashPop { index }),
+                    KeyCode::Char('d') => self.confirm(Action::StashDrop { index }),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Navigation and loading tests against an in-memory commit DAG.
+
+use reki::git::{CommitInfo, GraphRow, Identity, MemoryRepo, Oid, Trailer, Uncommitted};
+
+use super::{App, CommitList, Row};
+
+/// A straight line of `n` commits `c0` (oldest) to `c{n-1}`, loaded
+/// `batch` at a time.
+fn linear(n: usize, batch: usize) -> App<MemoryRepo> {
+    let mut repo = MemoryRepo::new().batch_size(batch);
+    for i in 0..n {
+        let parent = i.checked_sub(1).map(|p| format!("c{p}"));
+        let parents: Vec<&str> = parent.iter().map(String::as_str).collect();
+        repo = repo.commit(&format!("c{i}"), &parents);
+    }
+    let mut app = App::with_source(repo).unwrap();
+    app.page_height = 5;
+    app
+}
+
+#[test]
+fn loads_the_next_batch_within_a_page_of_the_end() {
+    let mut app = linear(25, 10);
+    assert_eq!(app.commits.len(), 10);
+    assert_eq!(app.commits.get(0).unwrap().summary(), "c24");
+
+    app.move_down(4);
+    assert_eq!(app.commits.len(), 10);
+    app.move_down(1);
+    assert_eq!(app.selected, 5);
+    assert_eq!(app.commits.len(), 20);
+
+    app.move_down(100);
+    assert_eq!(app.selected, 19);
+    assert_eq!(app.commits.len(), 25);
+    app.move_down(100);
+    assert_eq!(app.selected, 24);
+    assert!(app.all_loaded);
+}
+
+#[test]
+fn jumps_to_the_end_loading_everything() {
+    let mut app = linear(25, 10);
+    app.jump_to_end();
+    assert!(app.all_loaded);
+    assert_eq!(app.commits.len(), 25);
+    assert_eq!(app.selected, 24);
+    assert_eq!(app.commits.get(24).unwrap().summary(), "c0");
+
+    app.move_up(30);
+    assert_eq!(app.selected, 0);
+}
+
+#[test]
+fn selects_commits_beyond_the_loaded_batches() {
+    let mut app = linear(25, 10);
+    assert!(app.select_oid(MemoryRepo::id("c2")));
+    assert_eq!(app.selected, 22);
+    assert_eq!(app.commits.len(), 25);
+
+    assert!(!app.select_oid(MemoryRepo::id("elsewhere")));
+    assert_eq!(app.selected, 22);
+}
+
+#[test]
+fn keeps_the_selection_across_reloads() {
+    let mut app = linear(25, 10);
+    app.move_down(9);
+    app.move_down(3);
+    app.reload();
+    assert_eq!(app.selected, 12);
+    assert_eq!(app.commits.get(12).unwrap().summary(), "c12");
+}
+
+#[test]
+fn lays_out_branches_and_merges() {
+    let repo = MemoryRepo::new()
+        .commit("root", &[])
+        .commit("main-1", &["root"])
+        .commit("feat-1", &["root"])
+        .commit("main-2", &["main-1"])
+        .commit("merge", &["main-2", "feat-1"])
+        .branch("main", "merge")
+        .branch("feat", "feat-1")
+        .head("merge");
+    let app = App::with_source(repo).unwrap();
+
+    let rows: Vec<String> = app
+        .commits
+        .iter()
+        .map(|c| format!("{}{}", c.graph(), c.summary()))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            "* merge",
+            "* | main-2",
+            "| * feat-1",
+            "* | main-1",
+            "* | root",
+        ]
+    );
+
+    let refs: Vec<&str> = app
+        .refs_for(MemoryRepo::id("merge"))
+        .iter()
+        .map(|r| r.name.as_str())
+        .collect();
+    assert_eq!(refs, ["main", "HEAD"]);
+}
+
+#[test]
+fn stores_rows_compactly() {
+    let person = |name: &str, raw: Option<&str>, time| Identity {
+        name: name.to_string(),
+        email: format!("{name}@x.org"),
+        raw: raw.map(|r| (r.to_string(), format!("{r}@x.org"))),
+        time,
+    };
+    let commit = |id: u8, author: Identity, committer: Identity, trailers| CommitInfo {
+        id: Oid::from_bytes(&[id; 20]).unwrap(),
+        parent_ids: vec![Oid::zero(); usize::from(id)],
+        summary: format!("summary {id}"),
+        author,
+        committer,
+        trailers,
+        uncommitted: None,
+    };
+    let co_author = Trailer {
+        key: "Co-authored-by".to_string(),
+        value: "Cy <cy@x.org>".to_string(),
+    };
+
+    let mut list = CommitList::default();
+    let al = person("Al", Some("al"), 100);
+    list.push(
+        &commit(1, al.clone(), al.clone(), vec![co_author]),
+        GraphRow {
+            column: 1,
+            lanes: 2,
+        },
+    );
+    list.push(
+        &commit(2, al, person("Bo", None, 100), Vec::new()),
+        GraphRow {
+            column: 0,
+            lanes: 3,
+        },
+    );
+    let unstaged = CommitInfo {
+        uncommitted: Some(Uncommitted::Unstaged),
+        ..commit(
+            0,
+            person("Uncommitted", None, 0),
+            person("Uncommitted", None, 0),
+            Vec::new(),
+        )
+    };
+    list.push(
+        &unstaged,
+        GraphRow {
+            column: 0,
+            lanes: 1,
+        },
+    );
+
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.graph_width(), 6);
+    let rows: Vec<Row> = list.iter().collect();
+    assert_eq!(rows[0].summary(), "summary 1");
+    assert_eq!(rows[1].summary(), "summary 2");
+    assert_eq!(rows[0].graph().to_string(), "| * ");
+    assert_eq!(
+        (rows[0].name(false, false), rows[0].name(false, true)),
+        ("Al", "al")
+    );
+    assert_eq!(
+        (rows[1].name(true, false), rows[1].name(true, true)),
+        ("Bo", "Bo")
+    );
+    assert_eq!(rows[0].co_author_count(), 1);
+    assert_eq!(rows[1].parent_count(), 2);
+    assert!(!rows[0].rewritten() && rows[1].rewritten());
+    assert_eq!(rows[0].uncommitted(), None);
+    assert_eq!(rows[2].uncommitted(), Some(Uncommitted::Unstaged));
+    assert_eq!(list.position(rows[1].id(), 0), Some(1));
+    assert_eq!(list.position(rows[1].id(), 2), None);
+
+    list.clear();
+    assert!(list.is_empty() && list.get(0).is_none());
+}
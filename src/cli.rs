@@ -0,0 +1,174 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use reki::git::{Backend, Order, Repo, TrailerFilter};
+
+use crate::export::ExportFormat;
+
+const USAGE: &str = "\
+Usage: reki [OPTIONS] [<REVISION-RANGE>]
+
+Arguments:
+  [<REVISION-RANGE>]       Show this range (`main..feat`) or revision instead of HEAD
+
+Options:
+      --trailer <FILTER>   Only show commits with a trailer `Key` or `Key: value`
+      --topo-order         Show no commit before its children, keeping branches together
+      --print              Print the log as text instead of opening the viewer
+                           (the default when stdout is not a terminal)
+  -n, --max-count <N>      Print or export at most N commits
+      --color[=<WHEN>]     Color printed output: auto, always, never [default: auto]
+      --export <FORMAT>    Write the log to stdout as json, ndjson or csv
+      --full-message       Include the full commit message in exported records
+      --yank-to <FILE>     On exit, also write the last yanked text to FILE (`-` for stdout)
+      --backend <NAME>     Load commits with git2 or gix [default: git2]
+  -h, --help               Print help";
+
+/// Where yanked text is written on exit, for terminals without OSC 52.
+pub enum YankTarget {
+    Stdout,
+    File(PathBuf),
+}
+
+/// When printed output is colored.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Only when stdout is a terminal and `NO_COLOR` is unset.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Command-line options.
+#[derive(Default)]
+pub struct Args {
+    pub yank_to: Option<YankTarget>,
+    /// Print the log instead of starting the TUI.
+    pub print: bool,
+    /// Maximum number of commits to print.
+    pub max_count: Option<usize>,
+    pub color: ColorMode,
+    /// Revision range to walk instead of HEAD.
+    pub range: Option<String>,
+    pub trailer: Option<TrailerFilter>,
+    pub order: Order,
+    /// Export the log in this format instead of starting the TUI.
+    pub export: Option<ExportFormat>,
+    /// Include full messages in exported records.
+    pub full_message: bool,
+    /// Library commits and refs are loaded with.
+    pub backend: Backend,
+}
+
+impl Args {
+    /// Parse the process arguments. Prints usage and exits on `--help`.
+    pub fn parse() -> Result<Self> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    /// Open the repository in the current directory, with the range and
+    /// filter from the command line applied.
+    pub fn open_repo(&self) -> Result<Repo> {
+        let mut repo = Repo::open_with(&std::env::current_dir()?, self.backend)?;
+        if let Some(range) = &self.range
+            && let Err(e) = repo.set_range(Some(range.clone()))
+        {
+            bail!("bad revision range '{range}': {e}");
+        }
+        repo.set_filter(self.trailer.clone());
+        repo.set_order(self.order);
+        Ok(repo)
+    }
+
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Accept `--opt=value` and `-n10` as well as separate values.
+            let (arg, inline) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => {
+                    (name.to_string(), Some(value.to_string()))
+                }
+                _ if arg.starts_with("-n") && arg.len() > 2 => {
+                    ("-n".to_string(), Some(arg[2..].to_string()))
+                }
+                _ => (arg, None),
+            };
+            match arg.as_str() {
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                "--print" => parsed.print = true,
+                "--full-message" => parsed.full_message = true,
+                "--topo-order" => parsed.order = Order::Topo,
+                "--trailer" => {
+                    let Some(value) = inline.or_else(|| args.next()) else {
+                        bail!("--trailer requires a value\n\n{USAGE}");
+                    };
+                    let Some(filter) = TrailerFilter::parse(&value) else {
+                        bail!("invalid trailer filter '{value}'\n\n{USAGE}");
+                    };
+                    parsed.trailer = Some(filter);
+                }
+                "--export" => {
+                    let Some(value) = inline.or_else(|| args.next()) else {
+                        bail!("--export requires a value\n\n{USAGE}");
+                    };
+                    let Some(format) = ExportFormat::parse(&value) else {
+                        bail!("unknown export format '{value}'\n\n{USAGE}");
+                    };
+                    parsed.export = Some(format);
+                }
+                "-n" | "--max-count" => {
+                    let Some(value) = inline.or_else(|| args.next()) else {
+                        bail!("{arg} requires a value\n\n{USAGE}");
+                    };
+                    let Ok(n) = value.parse() else {
+                        bail!("invalid count '{value}' for {arg}\n\n{USAGE}");
+                    };
+                    parsed.max_count = Some(n);
+                }
+                "--color" => {
+                    parsed.color = match inline.as_deref() {
+                        None | Some("always") => ColorMode::Always,
+                        Some("never") => ColorMode::Never,
+                        Some("auto") => ColorMode::Auto,
+                        Some(other) => bail!("invalid value '{other}' for --color\n\n{USAGE}"),
+                    };
+                }
+                "--yank-to" => {
+                    let Some(value) = inline.or_else(|| args.next()) else {
+                        bail!("--yank-to requires a value\n\n{USAGE}");
+                    };
+                    parsed.yank_to = Some(if value == "-" {
+                        YankTarget::Stdout
+                    } else {
+                        YankTarget::File(PathBuf::from(value))
+                    });
+                }
+                "--backend" => {
+                    let Some(value) = inline.or_else(|| args.next()) else {
+                        bail!("--backend requires a value\n\n{USAGE}");
+                    };
+                    parsed.backend = match value.as_str() {
+                        "git2" => Backend::Git2,
+                        #[cfg(feature = "gix")]
+                        "gix" => Backend::Gix,
+                        #[cfg(not(feature = "gix"))]
+                        "gix" => {
+                            bail!("the gix backend is not built in; rebuild with `--features gix`")
+                        }
+                        other => bail!("unknown backend '{other}'\n\n{USAGE}"),
+                    };
+                }
+                range if !range.starts_with('-') && parsed.range.is_none() => {
+                    parsed.range = Some(range.to_string());
+                }
+                other => bail!("unexpected argument '{other}'\n\n{USAGE}"),
+            }
+        }
+        Ok(parsed)
+    }
+}
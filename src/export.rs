@@ -0,0 +1,224 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use reki::git::{CommitInfo, RefDecoration, Repo};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// Serialization format of an export.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON array of records.
+    Json,
+    /// One JSON record per line.
+    Ndjson,
+    /// Comma-separated values with a header row.
+    Csv,
+}
+
+impl ExportFormat {
+    /// Parse a format name (`json`, `ndjson` / `jsonl`, `csv`).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(ExportFormat::Json),
+            "ndjson" | "jsonl" => Some(ExportFormat::Ndjson),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    /// Pick the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::parse(path.extension()?.to_str()?)
+    }
+}
+
+/// Column names, in record order.
+const FIELDS: [&str; 11] = [
+    "hash",
+    "parents",
+    "author_name",
+    "author_email",
+    "author_date",
+    "committer_name",
+    "committer_email",
+    "committer_date",
+    "refs",
+    "summary",
+    "message",
+];
+
+/// Write the commits of the log (its range and filter applied, at most
+/// `limit`) to `out`, one record at a time. `message` adds the full message
+/// to each record. Returns the number of records written.
+pub fn export(
+    repo: &Repo,
+    format: ExportFormat,
+    message: bool,
+    limit: Option<usize>,
+    out: &mut impl Write,
+) -> Result<usize> {
+    let fields = if message {
+        &FIELDS[..]
+    } else {
+        &FIELDS[..FIELDS.len() - 1]
+    };
+
+    match format {
+        ExportFormat::Json => out.write_all(b"[")?,
+        ExportFormat::Ndjson => {}
+        ExportFormat::Csv => writeln!(out, "{}", fields.join(","))?,
+    }
+
+    let mut count = 0;
+    for c in repo.walk()?.take(limit.unwrap_or(usize::MAX)) {
+        let c = c?;
+        let full_message = if message {
+            repo.message(c.id)
+                .unwrap_or_default()
+                .trim_end()
+                .to_string()
+        } else {
+            String::new()
+        };
+        let values = record(&c, repo.refs_for(c.id), full_message);
+        let values = &values[..fields.len()];
+        match format {
+            ExportFormat::Json | ExportFormat::Ndjson => {
+                if format == ExportFormat::Json {
+                    out.write_all(if count == 0 { b"\n" } else { b",\n" })?;
+                }
+                out.write_all(b"{")?;
+                for (i, (field, value)) in fields.iter().zip(values).enumerate() {
+                    if i > 0 {
+                        out.write_all(b",")?;
+                    }
+                    write!(out, "\"{field}\":{}", value.to_json())?;
+                }
+                out.write_all(b"}")?;
+                if format == ExportFormat::Ndjson {
+                    out.write_all(b"\n")?;
+                }
+            }
+            ExportFormat::Csv => {
+                let row: Vec<String> = values.iter().map(Value::to_csv).collect();
+                writeln!(out, "{}", row.join(","))?;
+            }
+        }
+        count += 1;
+    }
+
+    if format == ExportFormat::Json {
+        out.write_all(if count == 0 { b"]\n" } else { b"\n]\n" })?;
+    }
+    out.flush()?;
+    Ok(count)
+}
+
+/// A field value: a string, or a list (a JSON array, space-separated in CSV).
+enum Value {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl Value {
+    fn to_json(&self) -> String {
+        match self {
+            Value::Text(s) => json_string(s),
+            Value::List(items) => {
+                let items: Vec<String> = items.iter().map(|s| json_string(s)).collect();
+                format!("[{}]", items.join(","))
+            }
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        match self {
+            Value::Text(s) => csv_field(s),
+            Value::List(items) => csv_field(&items.join(" ")),
+        }
+    }
+}
+
+/// Field values of one commit, in [`FIELDS`] order.
+fn record(c: &CommitInfo, refs: &[RefDecoration], message: String) -> [Value; 11] {
+    [
+        Value::Text(c.id.to_string()),
+        Value::List(c.parent_ids.iter().map(ToString::to_string).collect()),
+        Value::Text(c.author.name.clone()),
+        Value::Text(c.author.email.clone()),
+        Value::Text(rfc3339(c.author.time)),
+        Value::Text(c.committer.name.clone()),
+        Value::Text(c.committer.email.clone()),
+        Value::Text(rfc3339(c.committer.time)),
+        Value::List(refs.iter().map(|r| r.name.clone()).collect()),
+        Value::Text(c.summary.clone()),
+        Value::Text(message),
+    ]
+}
+
+/// Format a Unix timestamp as an RFC 3339 UTC date.
+fn rfc3339(timestamp: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .and_then(|t| t.format(&Rfc3339).ok())
+        .unwrap_or_default()
+}
+
+/// Quote and escape `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Quote `s` for CSV if it contains a separator, quote or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_json_strings() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(
+            json_string("say \"hi\"\\\n\t\u{1}"),
+            "\"say \\\"hi\\\"\\\\\\n\\t\\u0001\""
+        );
+    }
+
+    #[test]
+    fn quotes_csv_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn picks_format_from_extension() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("log.JSONL")),
+            Some(ExportFormat::Ndjson)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("log.txt")), None);
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::OnceLock;
+
+use reki::git::{CommitDetails, Oid, SignatureStatus};
+use time::format_description::BorrowedFormatItem;
+use time::{OffsetDateTime, UtcOffset, macros::format_description};
+
+/// How many paths a one-line message lists before summarizing the rest.
+const MAX_LISTED_PATHS: usize = 5;
+
+/// Offset that dates are shown in; UTC until [`init_local_offset`] runs.
+static LOCAL_OFFSET: OnceLock<UtcOffset> = OnceLock::new();
+
+/// Look up the local timezone once, at startup. The `time` crate refuses to
+/// read it once other threads may be running, and tests skip this call so
+/// that their output does not depend on `TZ`.
+pub fn init_local_offset() {
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    let _ = LOCAL_OFFSET.set(offset);
+}
+
+/// Format a commit as `abc1234 ("summary", YYYY-MM-DD)` with the date of
+/// `timestamp` in the local timezone, like `git log --pretty=reference`.
+pub fn format_reference(id: Oid, summary: &str, timestamp: i64) -> String {
+    const FMT: &[BorrowedFormatItem<'_>] = format_description!("[year]-[month]-[day]");
+
+    let date = format_local(timestamp, FMT).unwrap_or_else(|| String::from("????-??-??"));
+    format!("{id:.7} (\"{summary}\", {date})")
+}
+
+/// Format a Unix timestamp as "YYYY-MM-DD HH:MM" in the local timezone.
+pub fn format_time(timestamp: i64) -> String {
+    const FMT: &[BorrowedFormatItem<'_>] =
+        format_description!("[year]-[month]-[day] [hour]:[minute]");
+
+    format_local(timestamp, FMT).unwrap_or_else(|| String::from("????-??-?? ??:??"))
+}
+
+/// Format a Unix timestamp in the local timezone.
+fn format_local(timestamp: i64, fmt: &[BorrowedFormatItem<'_>]) -> Option<String> {
+    let utc = OffsetDateTime::from_unix_timestamp(timestamp).ok()?;
+    let local_offset = LOCAL_OFFSET.get().copied().unwrap_or(UtcOffset::UTC);
+    utc.to_offset(local_offset).format(&fmt).ok()
+}
+
+/// How a line of the commit view header is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DetailKind {
+    /// `commit <hash>` / `tag <name>` heading.
+    Title,
+    /// `Key: value` header field.
+    Field,
+    /// Commit or tag message.
+    Message,
+    /// Signature block.
+    Signature,
+}
+
+/// One line of the commit view header.
+pub struct DetailLine {
+    /// How the line is styled.
+    pub kind: DetailKind,
+    /// Line content without a trailing newline.
+    pub text: String,
+}
+
+/// Lay out `details` like `git show`, followed by one section per tag.
+pub fn detail_lines(details: &CommitDetails) -> Vec<DetailLine> {
+    let mut out = Vec::new();
+    let mut push = |kind, text: String| out.push(DetailLine { kind, text });
+
+    push(DetailKind::Title, format!("commit {}", details.id));
+    if details.parent_ids.len() > 1 {
+        let parents: Vec<String> = details
+            .parent_ids
+            .iter()
+            .map(|p| format!("{p:.7}"))
+            .collect();
+        push(
+            DetailKind::Field,
+            format!("Merge:      {}", parents.join(" ")),
+        );
+    }
+    push(DetailKind::Field, format!("Author:     {}", details.author));
+    push(
+        DetailKind::Field,
+        format!("AuthorDate: {}", format_time(details.author.time)),
+    );
+    push(
+        DetailKind::Field,
+        format!("Commit:     {}", details.committer),
+    );
+    push(
+        DetailKind::Field,
+        format!("CommitDate: {}", format_time(details.committer.time)),
+    );
+    let sig = &details.signature;
+    if sig.status != SignatureStatus::Unsigned {
+        let signer = sig
+            .signer
+            .as_ref()
+            .map_or_else(String::new, |s| format!(" by {s}"));
+        push(
+            DetailKind::Field,
+            format!(
+                "Signature:  {} {} signature{signer}",
+                sig.status.describe(),
+                sig.format
+            ),
+        );
+        for line in sig.output.trim_end().lines() {
+            push(DetailKind::Signature, format!("    {line}"));
+        }
+    }
+    for t in &details.trailers {
+        push(DetailKind::Field, format!("{}: {}", t.key, t.value));
+    }
+    push(DetailKind::Message, String::new());
+    for line in details.message.trim_end().lines() {
+        push(DetailKind::Message, format!("    {line}"));
+    }
+
+    for tag in &details.tags {
+        push(DetailKind::Message, String::new());
+        let Some(message) = &tag.message else {
+            push(DetailKind::Title, format!("tag {} (lightweight)", tag.name));
+            continue;
+        };
+        push(DetailKind::Title, format!("tag {}", tag.name));
+        if let Some(tagger) = &tag.tagger {
+            push(DetailKind::Field, format!("Tagger:     {tagger}"));
+            push(
+                DetailKind::Field,
+                format!("TaggerDate: {}", format_time(tagger.time)),
+            );
+        }
+        push(DetailKind::Message, String::new());
+        for line in message.trim_end().lines() {
+            push(DetailKind::Message, format!("    {line}"));
+        }
+        if let Some(signature) = &tag.signature {
+            for line in signature.trim_end().lines() {
+                push(DetailKind::Signature, format!("    {line}"));
+            }
+        }
+    }
+    push(DetailKind::Message, String::new());
+    out
+}
+
+/// Join paths for a one-line message, summarizing long lists.
+pub fn list_paths(paths: &[String]) -> String {
+    let mut listed = paths
+        .iter()
+        .take(MAX_LISTED_PATHS)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if paths.len() > MAX_LISTED_PATHS {
+        listed.push_str(&format!(" and {} more", paths.len() - MAX_LISTED_PATHS));
+    }
+    listed
+}
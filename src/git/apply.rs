@@ -0,0 +1,262 @@
+use git2::build::CheckoutBuilder;
+use git2::{CherrypickOptions, RevertOptions};
+
+use super::{Error, Oid, Repo, Result};
+
+/// Result of applying a commit's changes onto HEAD.
+pub enum ApplyOutcome {
+    /// The changes applied cleanly and were committed as this commit.
+    Committed(Oid),
+    /// The index has conflicts in these paths; HEAD is unchanged and the
+    /// repository is left mid-operation until resolved or aborted.
+    Conflicts(Vec<String>),
+}
+
+impl Repo {
+    /// Cherry-pick `target` onto HEAD. For merge commits, `mainline` is the
+    /// 1-based parent to diff against; it is ignored for regular commits.
+    pub fn cherry_pick(&mut self, target: Oid, mainline: u32) -> Result<ApplyOutcome> {
+        self.ensure_clean_index()?;
+        let commit = self.inner.find_commit(target.git())?;
+        let mut opts = CherrypickOptions::new();
+        if commit.parent_count() > 1 {
+            opts.mainline(mainline);
+        }
+        self.inner.cherrypick(&commit, Some(&mut opts))?;
+
+        let message = commit.message().unwrap_or("");
+        self.commit_applied(&commit.author(), message)
+    }
+
+    /// Revert `target` on top of HEAD. For merge commits, `mainline` is the
+    /// 1-based parent whose side is kept; it is ignored for regular commits.
+    pub fn revert(&mut self, target: Oid, mainline: u32) -> Result<ApplyOutcome> {
+        self.ensure_clean_index()?;
+        let commit = self.inner.find_commit(target.git())?;
+        let mut opts = RevertOptions::new();
+        if commit.parent_count() > 1 {
+            opts.mainline(mainline);
+        }
+        self.inner.revert(&commit, Some(&mut opts))?;
+
+        let message = format!(
+            "Revert \"{}\"\n\nThis reverts commit {}.\n",
+            commit.summary().unwrap_or(""),
+            commit.id()
+        );
+        let author = self.inner.signature()?;
+        self.commit_applied(&author, &message)
+    }
+
+    /// Abort an in-progress cherry-pick or revert: restore the paths it
+    /// touched to HEAD and clear the operation state. Changes to other
+    /// files in the working tree are kept.
+    pub fn abort_apply(&mut self) -> Result<()> {
+        let head = self.inner.head().and_then(|h| h.peel_to_commit())?;
+        // The index was clean when the operation started, so everything it
+        // now holds beyond HEAD, conflicts included, came from it.
+        let diff = self
+            .inner
+            .diff_tree_to_index(Some(&head.tree()?), None, None)?;
+        let mut paths = self.conflicted_paths()?;
+        paths.extend(diff.deltas().filter_map(|d| {
+            let path = d.new_file().path().or(d.old_file().path())?;
+            Some(path.to_string_lossy().into_owned())
+        }));
+        if !paths.is_empty() {
+            self.inner.reset_default(Some(head.as_object()), &paths)?;
+            let mut checkout = CheckoutBuilder::new();
+            checkout.force().remove_untracked(true);
+            for path in &paths {
+                checkout.path(path);
+            }
+            self.inner.checkout_head(Some(&mut checkout))?;
+        }
+        self.inner.cleanup_state()?;
+        Ok(())
+    }
+
+    /// Paths with conflicts in the index.
+    pub fn conflicted_paths(&self) -> Result<Vec<String>> {
+        let index = self.inner.index()?;
+        let mut paths: Vec<String> = index
+            .conflicts()?
+            .filter_map(|c| c.ok())
+            .filter_map(|c| c.our.or(c.their).or(c.ancestor))
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .collect();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    /// Refuse to start when staged changes would be swept into the new commit.
+    fn ensure_clean_index(&self) -> Result<()> {
+        let head_tree = self.inner.head().and_then(|h| h.peel_to_tree())?;
+        let diff = self
+            .inner
+            .diff_tree_to_index(Some(&head_tree), None, None)?;
+        if diff.deltas().len() > 0 {
+            return Err(Error::DirtyIndex);
+        }
+        Ok(())
+    }
+
+    /// Commit the index produced by a cherry-pick or revert on top of HEAD,
+    /// or report its conflicts.
+    fn commit_applied(&self, author: &git2::Signature<'_>, message: &str) -> Result<ApplyOutcome> {
+        let mut index = self.inner.index()?;
+        if index.has_conflicts() {
+            return Ok(ApplyOutcome::Conflicts(self.conflicted_paths()?));
+        }
+
+        let tree_id = index.write_tree()?;
+        let tree = self.inner.find_tree(tree_id)?;
+        let head = self.inner.head().and_then(|h| h.peel_to_commit())?;
+        if head.tree_id() == tree_id {
+            self.inner.cleanup_state()?;
+            return Err(Error::NothingToCommit);
+        }
+        let committer = self.inner.signature()?;
+        let id = self
+            .inner
+            .commit(Some("HEAD"), author, &committer, message, &tree, &[&head])?;
+        self.inner.cleanup_state()?;
+        Ok(ApplyOutcome::Committed(Oid::from_git(id)))
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::path::Path;
+
+    use git2::build::CheckoutBuilder;
+    use git2::{Repository, Signature, Time};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// A repository in a temp dir with a committer identity configured.
+    pub(in crate::git) fn repo() -> (TempDir, Repository) {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "T").unwrap();
+        config.set_str("user.email", "t@x.org").unwrap();
+        (dir, repo)
+    }
+
+    /// Commit the first parent's tree with `files` written (`None`
+    /// removes), without moving any ref.
+    pub(in crate::git) fn commit(
+        repo: &Repository,
+        message: &str,
+        parents: &[Oid],
+        files: &[(&str, Option<&str>)],
+    ) -> Oid {
+        let parents: Vec<_> = parents
+            .iter()
+            .map(|p| repo.find_commit(p.git()).unwrap())
+            .collect();
+        let base = parents.first().map(|p| p.tree().unwrap());
+        let mut tree = repo.treebuilder(base.as_ref()).unwrap();
+        for (path, content) in files {
+            match content {
+                Some(content) => {
+                    let blob = repo.blob(content.as_bytes()).unwrap();
+                    tree.insert(path, blob, 0o100644).unwrap();
+                }
+                None => tree.remove(path).unwrap(),
+            }
+        }
+        let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+        let sig = Signature::new("T", "t@x.org", &Time::new(1_700_000_000, 0)).unwrap();
+        let parents: Vec<_> = parents.iter().collect();
+        let id = repo.commit(None, &sig, &sig, message, &tree, &parents);
+        Oid::from_git(id.unwrap())
+    }
+
+    /// Point `main` at `id` and check it out, overwriting the worktree.
+    pub(in crate::git) fn check_out(repo: &Repository, id: Oid) {
+        repo.reference("refs/heads/main", id.git(), true, "test")
+            .unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        repo.checkout_head(Some(CheckoutBuilder::new().force()))
+            .unwrap();
+    }
+
+    fn read(dir: &TempDir, path: &str) -> Option<String> {
+        std::fs::read_to_string(dir.path().join(path)).ok()
+    }
+
+    #[test]
+    fn abort_keeps_unrelated_changes() {
+        let (dir, git) = repo();
+        let base = commit(&git, "base", &[], &[("a", Some("1\n")), ("b", Some("x\n"))]);
+        let ours = commit(&git, "ours", &[base], &[("a", Some("2\n"))]);
+        let theirs = commit(
+            &git,
+            "theirs",
+            &[base],
+            &[("a", Some("3\n")), ("c", Some("new\n"))],
+        );
+        check_out(&git, ours);
+        std::fs::write(dir.path().join("b"), "edited\n").unwrap();
+
+        let mut repo = Repo::open(dir.path()).unwrap();
+        let Ok(ApplyOutcome::Conflicts(paths)) = repo.cherry_pick(theirs, 1) else {
+            panic!("expected conflicts");
+        };
+        assert_eq!(paths, ["a"]);
+        assert_eq!(read(&dir, "c").as_deref(), Some("new\n"));
+
+        repo.abort_apply().unwrap();
+        assert_eq!(read(&dir, "a").as_deref(), Some("2\n"));
+        assert_eq!(read(&dir, "b").as_deref(), Some("edited\n"));
+        assert_eq!(read(&dir, "c"), None);
+        assert_eq!(git.state(), git2::RepositoryState::Clean);
+        let index = git.index().unwrap();
+        assert!(!index.has_conflicts());
+        assert!(index.get_path(Path::new("c"), 0).is_none());
+        assert_eq!(git.head().unwrap().target(), Some(ours.git()));
+    }
+
+    #[test]
+    fn applies_merges_against_the_chosen_parent() {
+        let (dir, git) = repo();
+        let base = commit(&git, "base", &[], &[("a", Some("1\n"))]);
+        let main = commit(&git, "main", &[base], &[("a", Some("2\n"))]);
+        let feature = commit(&git, "feature", &[base], &[("f", Some("f\n"))]);
+        let merge = commit(&git, "merge", &[main, feature], &[("f", Some("f\n"))]);
+        let mut repo = Repo::open(dir.path()).unwrap();
+
+        // Against the first parent, a merge brings in the feature side.
+        check_out(&git, base);
+        let Ok(ApplyOutcome::Committed(_)) = repo.cherry_pick(merge, 1) else {
+            panic!("expected a commit");
+        };
+        assert_eq!(read(&dir, "a").as_deref(), Some("1\n"));
+        assert_eq!(read(&dir, "f").as_deref(), Some("f\n"));
+
+        // Reverting keeps the chosen parent's side.
+        check_out(&git, merge);
+        let Ok(ApplyOutcome::Committed(id)) = repo.revert(merge, 1) else {
+            panic!("expected a commit");
+        };
+        assert_eq!(
+            (read(&dir, "a").as_deref(), read(&dir, "f")),
+            (Some("2\n"), None)
+        );
+        let message = git
+            .find_commit(id.git())
+            .unwrap()
+            .message()
+            .unwrap()
+            .to_string();
+        assert!(message.starts_with("Revert \"merge\""));
+
+        check_out(&git, merge);
+        repo.revert(merge, 2).unwrap();
+        assert_eq!(read(&dir, "a").as_deref(), Some("1\n"));
+        assert_eq!(read(&dir, "f").as_deref(), Some("f\n"));
+    }
+}
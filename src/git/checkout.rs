@@ -0,0 +1,54 @@
+use git2::{BranchType, build::CheckoutBuilder};
+
+use super::{Error, Oid, Repo, Result};
+
+impl Repo {
+    /// Check out `target` with a detached HEAD.
+    pub fn checkout_commit(&mut self, target: Oid) -> Result<()> {
+        {
+            let commit = self.inner.find_commit(target.git())?;
+            self.checkout_safe(commit.as_object())?;
+            self.inner.set_head_detached(target.git())?;
+        }
+        self.refresh_refs()
+    }
+
+    /// Check out the local branch `name` and make it the current branch.
+    pub fn checkout_branch(&mut self, name: &str) -> Result<()> {
+        {
+            let branch = self.inner.find_branch(name, BranchType::Local)?;
+            let refname = branch
+                .get()
+                .name()
+                .map(str::to_string)
+                .ok_or(Error::InvalidRefName)?;
+            let commit = branch.get().peel_to_commit()?;
+            self.checkout_safe(commit.as_object())?;
+            self.inner.set_head(&refname)?;
+        }
+        self.refresh_refs()
+    }
+
+    /// Update the index and working tree to `target` without overwriting
+    /// local changes. Fails with the list of conflicting paths if it would.
+    fn checkout_safe(&self, target: &git2::Object<'_>) -> Result<()> {
+        let mut conflicts = Vec::new();
+        let result = {
+            let mut opts = CheckoutBuilder::new();
+            opts.safe()
+                .notify_on(git2::CheckoutNotificationType::CONFLICT)
+                .notify(|_, path, _, _, _| {
+                    if let Some(path) = path {
+                        conflicts.push(path.to_string_lossy().into_owned());
+                    }
+                    true
+                });
+            self.inner.checkout_tree(target, Some(&mut opts))
+        };
+
+        if !conflicts.is_empty() {
+            return Err(Error::WouldOverwrite(conflicts));
+        }
+        Ok(result?)
+    }
+}
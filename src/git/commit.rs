@@ -1,22 +1,27 @@
 use std::fmt;
 
-use time::{OffsetDateTime, UtcOffset, macros::format_description};
+use super::Oid;
+use super::trailer::Trailer;
 
 /// Type of a git reference for display purposes.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RefKind {
     /// Local branch (refs/heads/*).
     Branch,
     /// Remote-tracking branch (refs/remotes/*).
     Remote,
-    /// Tag (refs/tags/*).
+    /// Lightweight tag (refs/tags/* pointing straight at a commit).
     Tag,
+    /// Annotated tag (refs/tags/* pointing at a tag object).
+    AnnotatedTag,
     /// HEAD (may be detached or symbolic).
     Head,
+    /// Stash entry, attached to the commit it was based on.
+    Stash,
 }
 
 /// A reference decoration attached to a commit.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RefDecoration {
     /// Short display name (e.g. "main", "origin/main", "v1.0").
     pub name: String,
@@ -24,36 +29,94 @@ pub struct RefDecoration {
     pub kind: RefKind,
 }
 
+/// Uncommitted changes shown as a pseudo-commit above HEAD.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Uncommitted {
+    /// Changes in the index relative to HEAD.
+    Staged,
+    /// Changes in the working tree relative to the index.
+    Unstaged,
+}
+
+impl Uncommitted {
+    /// Placeholder id so the row can take part in graph layout.
+    pub fn id(self) -> Oid {
+        let mut bytes = [0u8; 20];
+        bytes[19] = match self {
+            Uncommitted::Staged => 1,
+            Uncommitted::Unstaged => 2,
+        };
+        Oid::from_bytes(&bytes).unwrap_or_else(|_| Oid::zero())
+    }
+}
+
+/// Name, email and timestamp of an author, committer or tagger.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Identity {
+    /// Name, mapped through `.mailmap`.
+    pub name: String,
+    /// Email, mapped through `.mailmap`.
+    pub email: String,
+    /// Name and email as recorded, if `.mailmap` changed them.
+    pub raw: Option<(String, String)>,
+    /// Unix timestamp.
+    pub time: i64,
+}
+
+impl Identity {
+    /// Name to show: the `.mailmap` one, or the recorded one if `raw`.
+    pub fn display_name(&self, raw: bool) -> &str {
+        match &self.raw {
+            Some((name, _)) if raw => name,
+            _ => &self.name,
+        }
+    }
+
+    /// Whether both name the same person, after `.mailmap`.
+    pub fn same_person(&self, other: &Identity) -> bool {
+        self.name == other.name && self.email == other.email
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} <{}>", self.name, self.email)?;
+        if let Some((name, email)) = &self.raw {
+            write!(f, " (as {name} <{email}>)")?;
+        }
+        Ok(())
+    }
+}
+
 /// Represents a single git commit with the information needed to display.
+#[derive(Debug, PartialEq, Eq)]
 pub struct CommitInfo {
     /// Full commit hash (hex).
-    pub id: git2::Oid,
+    pub id: Oid,
     /// Parent commit IDs.
-    pub parent_ids: Vec<git2::Oid>,
+    pub parent_ids: Vec<Oid>,
     /// Commit summary (first line of message).
     pub summary: String,
-    /// Author name.
-    pub author: String,
-    /// Commit time as a Unix timestamp.
-    pub time: i64,
-    /// Reference decorations (branches, tags, HEAD) pointing to this commit.
-    pub refs: Vec<RefDecoration>,
+    /// Who wrote the change, and when.
+    pub author: Identity,
+    /// Who created this commit object, and when (differs from the author
+    /// after a rebase, cherry-pick or amend).
+    pub committer: Identity,
+    /// Trailers (`Signed-off-by`, `Co-authored-by`, ...) of the message.
+    pub trailers: Vec<Trailer>,
+    /// Set for the pseudo-commit rows of uncommitted changes.
+    pub uncommitted: Option<Uncommitted>,
 }
 
 impl CommitInfo {
-    /// Format the commit time as "YYYY-MM-DD HH:MM" in the local timezone.
-    pub fn formatted_time(&self) -> String {
-        const FMT: &[time::format_description::BorrowedFormatItem<'_>] =
-            format_description!("[year]-[month]-[day] [hour]:[minute]");
+    /// Number of `Co-authored-by` trailers.
+    pub fn co_author_count(&self) -> usize {
+        self.trailers.iter().filter(|t| t.is_co_author()).count()
+    }
 
-        let Ok(utc) = OffsetDateTime::from_unix_timestamp(self.time) else {
-            return String::from("????-??-?? ??:??");
-        };
-        let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
-        let local = utc.to_offset(local_offset);
-        local
-            .format(&FMT)
-            .unwrap_or_else(|_| String::from("????-??-?? ??:??"))
+    /// Whether author and committer differ in identity or time.
+    pub fn rewritten(&self) -> bool {
+        !self.author.same_person(&self.committer) || self.author.time != self.committer.time
     }
 }
 
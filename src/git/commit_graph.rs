@@ -0,0 +1,555 @@
+//! Reader for git's commit-graph file (`objects/info/commit-graph`) and for
+//! split commit-graph chains (`objects/info/commit-graphs/`). They store the
+//! parents, commit time and generation number of the commits they cover, so
+//! traversal does not have to inflate commit objects.
+
+use std::ops::Range;
+use std::path::Path;
+
+use super::Oid;
+
+const SIGNATURE: &[u8] = b"CGPH";
+const HASH_LEN: usize = 20;
+/// Width of a commit data record: tree id, two parent slots, generation and time.
+const DATA_LEN: usize = HASH_LEN + 16;
+/// Parent slot value for a missing parent.
+const NO_PARENT: u32 = 0x7000_0000;
+/// Set on the second parent slot when the parents continue in the extra
+/// edge list, on the last entry of that list, and on generation offsets
+/// stored in the overflow chunk.
+const HIGH_BIT: u32 = 0x8000_0000;
+
+/// Position of a commit in a [`CommitGraph`], counted across all layers of
+/// a split chain, base layer first.
+pub(super) type Position = u32;
+
+/// Size and modification time of the file that names a commit graph.
+pub(super) type Stamp = (u64, std::time::SystemTime);
+
+/// The commit graph of a repository: a single file or a chain of layers.
+pub(super) struct CommitGraph {
+    /// Base layer first.
+    layers: Vec<Layer>,
+    /// Whether every layer stores corrected commit dates.
+    corrected_dates: bool,
+}
+
+struct Layer {
+    data: Vec<u8>,
+    /// Number of commits in the layers below.
+    base: u32,
+    len: u32,
+    fanout: Range<usize>,
+    ids: Range<usize>,
+    commit_data: Range<usize>,
+    extra_edges: Option<Range<usize>>,
+    generation_data: Option<Range<usize>>,
+    generation_overflow: Option<Range<usize>>,
+}
+
+impl CommitGraph {
+    /// Read the commit graph of the object database at `objects`. `None`
+    /// if there is none, or if it is malformed or uses another hash.
+    pub(super) fn open(objects: &Path) -> Option<Self> {
+        let info = objects.join("info");
+        let layers = match std::fs::read(info.join("commit-graph")) {
+            Ok(data) => vec![Layer::parse(data, 0, 0)?],
+            Err(_) => {
+                let dir = info.join("commit-graphs");
+                let chain = std::fs::read_to_string(dir.join("commit-graph-chain")).ok()?;
+                let mut layers: Vec<Layer> = Vec::new();
+                for (depth, hash) in chain.lines().map(str::trim).enumerate() {
+                    let data = std::fs::read(dir.join(format!("graph-{hash}.graph"))).ok()?;
+                    let base = layers.last().map_or(0, |l| l.base + l.len);
+                    layers.push(Layer::parse(data, depth, base)?);
+                }
+                layers
+            }
+        };
+        if layers.is_empty() {
+            return None;
+        }
+        let corrected_dates = layers.iter().all(|l| l.generation_data.is_some());
+        Some(Self {
+            layers,
+            corrected_dates,
+        })
+    }
+
+    /// Size and modification time of the file that names the commit graph
+    /// of `objects`: `commit-graph`, or the chain file of a split graph,
+    /// whose layers are named by their hash. Tells whether the graph
+    /// changed since it was read.
+    pub(super) fn stamp(objects: &Path) -> Option<Stamp> {
+        let info = objects.join("info");
+        let meta = std::fs::metadata(info.join("commit-graph"))
+            .or_else(|_| std::fs::metadata(info.join("commit-graphs/commit-graph-chain")))
+            .ok()?;
+        Some((meta.len(), meta.modified().ok()?))
+    }
+
+    /// Position of commit `id`, if the graph covers it.
+    pub(super) fn find(&self, id: Oid) -> Option<Position> {
+        let id = id.as_bytes();
+        self.layers.iter().find_map(|layer| {
+            let first = usize::from(id[0]);
+            let lo = match first {
+                0 => 0,
+                _ => layer.u32_at(layer.fanout.start + (first - 1) * 4)?,
+            };
+            let hi = layer.u32_at(layer.fanout.start + first * 4)?;
+            let (mut lo, mut hi) = (lo, hi.min(layer.len));
+            while lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                match layer.id(mid)?.cmp(id) {
+                    std::cmp::Ordering::Less => lo = mid + 1,
+                    std::cmp::Ordering::Greater => hi = mid,
+                    std::cmp::Ordering::Equal => return Some(layer.base + mid),
+                }
+            }
+            None
+        })
+    }
+
+    /// Id of the commit at `pos`.
+    pub(super) fn id(&self, pos: Position) -> Option<Oid> {
+        let (layer, index) = self.locate(pos)?;
+        Oid::from_bytes(layer.id(index)?).ok()
+    }
+
+    /// Parents of the commit at `pos`, in order.
+    pub(super) fn parents(&self, pos: Position) -> Option<Vec<Position>> {
+        let (layer, index) = self.locate(pos)?;
+        let record = layer.record(index)?;
+        let mut parents = Vec::new();
+        let first = layer.u32_at(record + HASH_LEN)?;
+        if first == NO_PARENT {
+            return Some(parents);
+        }
+        parents.push(first);
+        match layer.u32_at(record + HASH_LEN + 4)? {
+            NO_PARENT => {}
+            second if second & HIGH_BIT == 0 => parents.push(second),
+            second => {
+                // An octopus merge: the rest are in the extra edge list.
+                let edges = layer.extra_edges.as_ref()?;
+                let mut at = edges.start + (second & !HIGH_BIT) as usize * 4;
+                loop {
+                    let edge = layer.u32_at(at).filter(|_| at < edges.end)?;
+                    parents.push(edge & !HIGH_BIT);
+                    if edge & HIGH_BIT != 0 {
+                        break;
+                    }
+                    at += 4;
+                }
+            }
+        }
+        let total = self.len();
+        parents.iter().all(|&p| p < total).then_some(parents)
+    }
+
+    /// Commit time of the commit at `pos`, in seconds since the epoch.
+    pub(super) fn commit_time(&self, pos: Position) -> Option<i64> {
+        let (layer, index) = self.locate(pos)?;
+        let record = layer.record(index)?;
+        let high = u64::from(layer.u32_at(record + HASH_LEN + 8)? & 0b11);
+        let low = u64::from(layer.u32_at(record + HASH_LEN + 12)?);
+        Some(((high << 32) | low) as i64)
+    }
+
+    /// Generation number of the commit at `pos`: its corrected commit date
+    /// if every layer stores those, else its topological level.
+    /// Either way it is greater than that of every ancestor.
+    pub(super) fn generation(&self, pos: Position) -> Option<u64> {
+        let (layer, index) = self.locate(pos)?;
+        if !self.corrected_dates {
+            let record = layer.record(index)?;
+            return Some(u64::from(layer.u32_at(record + HASH_LEN + 8)? >> 2));
+        }
+        let data = layer.generation_data.as_ref()?;
+        let offset = layer.u32_at(data.start + index as usize * 4)?;
+        let offset = if offset & HIGH_BIT == 0 {
+            u64::from(offset)
+        } else {
+            let overflow = layer.generation_overflow.as_ref()?;
+            let at = overflow.start + (offset & !HIGH_BIT) as usize * 8;
+            let bytes = layer
+                .data
+                .get(at..at + 8)
+                .filter(|_| at + 8 <= overflow.end)?;
+            u64::from_be_bytes(bytes.try_into().ok()?)
+        };
+        Some(self.commit_time(pos)? as u64 + offset)
+    }
+
+    fn len(&self) -> u32 {
+        self.layers.last().map_or(0, |l| l.base + l.len)
+    }
+
+    fn locate(&self, pos: Position) -> Option<(&Layer, u32)> {
+        let layer = self
+            .layers
+            .iter()
+            .find(|l| pos >= l.base && pos - l.base < l.len)?;
+        Some((layer, pos - layer.base))
+    }
+}
+
+impl Layer {
+    /// Parse one graph file, `depth` layers above the base of its chain.
+    fn parse(data: Vec<u8>, depth: usize, base: u32) -> Option<Self> {
+        let header = data.get(..8)?;
+        // Version 1, SHA-1 ids, and as many base layers as sit below.
+        if &header[..4] != SIGNATURE || header[4] != 1 || header[5] != 1 {
+            return None;
+        }
+        if usize::from(header[7]) != depth {
+            return None;
+        }
+
+        let mut chunks = Vec::new();
+        for i in 0..usize::from(header[6]) {
+            let entry = 8 + i * 12;
+            let id: [u8; 4] = data.get(entry..entry + 4)?.try_into().ok()?;
+            let start = u64_at(&data, entry + 4)?;
+            let end = u64_at(&data, entry + 16)?;
+            let (start, end) = (usize::try_from(start).ok()?, usize::try_from(end).ok()?);
+            if start > end || end > data.len() {
+                return None;
+            }
+            chunks.push((id, start..end));
+        }
+        let chunk = |id: &[u8; 4]| chunks.iter().find(|(c, _)| c == id).map(|(_, r)| r.clone());
+
+        let fanout = chunk(b"OIDF").filter(|r| r.len() == 256 * 4)?;
+        let len = u32::from_be_bytes(data.get(fanout.end - 4..fanout.end)?.try_into().ok()?);
+        let ids = chunk(b"OIDL").filter(|r| r.len() == len as usize * HASH_LEN)?;
+        let commit_data = chunk(b"CDAT").filter(|r| r.len() == len as usize * DATA_LEN)?;
+        let generation_data = chunk(b"GDA2");
+        if generation_data
+            .as_ref()
+            .is_some_and(|r| r.len() != len as usize * 4)
+        {
+            return None;
+        }
+        Some(Self {
+            base,
+            len,
+            fanout,
+            ids,
+            commit_data,
+            extra_edges: chunk(b"EDGE"),
+            generation_data,
+            generation_overflow: chunk(b"GDO2"),
+            data,
+        })
+    }
+
+    fn id(&self, index: u32) -> Option<&[u8]> {
+        let at = self.ids.start + index as usize * HASH_LEN;
+        self.data.get(at..at + HASH_LEN)
+    }
+
+    /// Offset of the commit data record of commit `index`.
+    fn record(&self, index: u32) -> Option<usize> {
+        (index < self.len).then(|| self.commit_data.start + index as usize * DATA_LEN)
+    }
+
+    fn u32_at(&self, at: usize) -> Option<u32> {
+        Some(u32::from_be_bytes(
+            self.data.get(at..at + 4)?.try_into().ok()?,
+        ))
+    }
+}
+
+fn u64_at(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use git2::{Oid, Repository, Signature, Time};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Write `layers` (commit ids, base layer first, each closed under
+    /// ancestry together with the layers below) as a commit graph: a single
+    /// file for one layer, a split chain for several. Corrected commit
+    /// dates are stored if `corrected`.
+    pub(in crate::git) fn write_graph(repo: &Repository, layers: &[Vec<Oid>], corrected: bool) {
+        let info = repo.path().join("objects/info");
+        let mut positions = HashMap::new();
+        let mut levels: HashMap<Oid, (u32, u64)> = HashMap::new();
+        let mut names = Vec::new();
+        for (depth, layer) in layers.iter().enumerate() {
+            // Layers list parents first, so levels are known by then.
+            for id in layer {
+                let commit = repo.find_commit(*id).unwrap();
+                let time = commit.time().seconds() as u64;
+                let (mut level, mut date) = (1, time);
+                for p in commit.parent_ids() {
+                    let (l, d) = levels[&p];
+                    level = level.max(l + 1);
+                    date = date.max(d + 1);
+                }
+                levels.insert(*id, (level, date));
+            }
+            let mut ids = layer.clone();
+            ids.sort();
+            for id in &ids {
+                positions.insert(*id, positions.len() as u32);
+            }
+
+            let (mut oids, mut data, mut edges) = (Vec::new(), Vec::new(), Vec::new());
+            let (mut generations, mut overflow) = (Vec::new(), Vec::new());
+            let mut fanout = [0u32; 256];
+            for id in &ids {
+                let commit = repo.find_commit(*id).unwrap();
+                let time = commit.time().seconds() as u64;
+                let parents: Vec<Oid> = commit.parent_ids().collect();
+                let (level, date) = levels[id];
+
+                for slot in &mut fanout[usize::from(id.as_bytes()[0])..] {
+                    *slot += 1;
+                }
+                oids.extend_from_slice(id.as_bytes());
+                data.extend_from_slice(commit.tree_id().as_bytes());
+                let slot = |i: usize| parents.get(i).map_or(NO_PARENT, |p| positions[p]);
+                data.extend(slot(0).to_be_bytes());
+                if parents.len() > 2 {
+                    data.extend((HIGH_BIT | (edges.len() / 4) as u32).to_be_bytes());
+                    for (i, p) in parents[1..].iter().enumerate() {
+                        let last = if i == parents.len() - 2 { HIGH_BIT } else { 0 };
+                        edges.extend((positions[p] | last).to_be_bytes());
+                    }
+                } else {
+                    data.extend(slot(1).to_be_bytes());
+                }
+                data.extend(((level << 2) | (time >> 32) as u32).to_be_bytes());
+                data.extend((time as u32).to_be_bytes());
+                let offset = date - time;
+                if offset < u64::from(HIGH_BIT) {
+                    generations.extend((offset as u32).to_be_bytes());
+                } else {
+                    generations.extend((HIGH_BIT | (overflow.len() / 8) as u32).to_be_bytes());
+                    overflow.extend(offset.to_be_bytes());
+                }
+            }
+
+            let mut chunks: Vec<(&[u8; 4], Vec<u8>)> = vec![
+                (
+                    b"OIDF",
+                    fanout.iter().flat_map(|n| n.to_be_bytes()).collect(),
+                ),
+                (b"OIDL", oids),
+                (b"CDAT", data),
+            ];
+            if corrected {
+                chunks.push((b"GDA2", generations));
+                if !overflow.is_empty() {
+                    chunks.push((b"GDO2", overflow));
+                }
+            }
+            if !edges.is_empty() {
+                chunks.push((b"EDGE", edges));
+            }
+            if depth > 0 {
+                chunks.push((b"BASE", vec![0; depth * HASH_LEN]));
+            }
+
+            let mut file = b"CGPH".to_vec();
+            file.extend([1, 1, chunks.len() as u8, depth as u8]);
+            let mut offset = (file.len() + (chunks.len() + 1) * 12) as u64;
+            for (id, chunk) in &chunks {
+                file.extend(id.iter());
+                file.extend(offset.to_be_bytes());
+                offset += chunk.len() as u64;
+            }
+            file.extend([0; 4]);
+            file.extend(offset.to_be_bytes());
+            for (_, chunk) in &chunks {
+                file.extend(chunk);
+            }
+            file.extend([0; HASH_LEN]);
+
+            if layers.len() == 1 {
+                std::fs::write(info.join("commit-graph"), file).unwrap();
+            } else {
+                let name = format!("{depth:040x}");
+                std::fs::create_dir_all(info.join("commit-graphs")).unwrap();
+                std::fs::write(info.join(format!("commit-graphs/graph-{name}.graph")), file)
+                    .unwrap();
+                names.push(name);
+            }
+        }
+        if layers.len() > 1 {
+            let chain = names.join("\n") + "\n";
+            std::fs::write(info.join("commit-graphs/commit-graph-chain"), chain).unwrap();
+        }
+    }
+
+    /// Commits `(name, parents, commit time)` in a new repository, oldest
+    /// first. Returns their ids in the same order.
+    pub(in crate::git) fn commits(dir: &Path, commits: &[(&str, &[usize], i64)]) -> Vec<Oid> {
+        let repo = Repository::init(dir).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let mut ids: Vec<Oid> = Vec::new();
+        for &(name, parents, time) in commits {
+            let sig = Signature::new("T", "t@x.org", &Time::new(time, 0)).unwrap();
+            let parents: Vec<_> = parents
+                .iter()
+                .map(|&p| repo.find_commit(ids[p]).unwrap())
+                .collect();
+            let parents: Vec<_> = parents.iter().collect();
+            ids.push(
+                repo.commit(None, &sig, &sig, name, &tree, &parents)
+                    .unwrap(),
+            );
+        }
+        repo.reference("refs/heads/main", *ids.last().unwrap(), true, "")
+            .unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        ids
+    }
+
+    /// Output of `git` run with `args` in `dir`, or `None` if git is not
+    /// installed.
+    pub(in crate::git) fn git(dir: &Path, args: &[&str]) -> Option<String> {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .ok()?;
+        assert!(output.status.success(), "git {args:?}: {output:?}");
+        Some(String::from_utf8(output.stdout).unwrap())
+    }
+
+    /// An octopus merge, then a commit made with a clock set far in the
+    /// past, and one whose clock is so far behind its parent's that its
+    /// corrected date offset does not fit 31 bits.
+    const HISTORY: &[(&str, &[usize], i64)] = &[
+        ("root", &[], 1_000),
+        ("a", &[0], 2_000),
+        ("b", &[0], 3_000),
+        ("c", &[0], 4_000),
+        ("octopus", &[1, 2, 3], 5_000),
+        ("skewed", &[4], 0),
+        ("future", &[5], 3_000_000_000),
+        ("epoch", &[6], 0),
+    ];
+
+    /// Corrected commit dates of [`HISTORY`].
+    const CORRECTED: [u64; 8] = [
+        1_000,
+        2_000,
+        3_000,
+        4_000,
+        5_000,
+        5_001,
+        3_000_000_000,
+        3_000_000_001,
+    ];
+
+    /// Topological levels of [`HISTORY`].
+    const LEVELS: [u64; 8] = [1, 2, 2, 2, 3, 4, 5, 6];
+
+    /// Check that `graph` holds exactly the commits `ids` as `repo` has them.
+    fn check_graph(repo: &Repository, graph: &CommitGraph, ids: &[Oid]) {
+        let ids: Vec<_> = ids.iter().map(|&id| super::Oid::from_git(id)).collect();
+        let pos: Vec<Position> = ids.iter().map(|id| graph.find(*id).unwrap()).collect();
+        let blob = Oid::hash_object(git2::ObjectType::Blob, b"x").unwrap();
+        assert!(graph.find(super::Oid::from_git(blob)).is_none());
+        for (i, id) in ids.iter().enumerate() {
+            let commit = repo.find_commit(id.git()).unwrap();
+            assert_eq!(graph.id(pos[i]), Some(*id));
+            assert_eq!(graph.commit_time(pos[i]), Some(commit.time().seconds()));
+            let parents: Vec<Oid> = graph
+                .parents(pos[i])
+                .unwrap()
+                .into_iter()
+                .map(|p| graph.id(p).unwrap().git())
+                .collect();
+            assert_eq!(parents, commit.parent_ids().collect::<Vec<_>>());
+        }
+    }
+
+    fn generations(graph: &CommitGraph, ids: &[Oid]) -> Vec<u64> {
+        ids.iter()
+            .map(|&id| graph.find(super::Oid::from_git(id)).unwrap())
+            .map(|pos| graph.generation(pos).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reads_single_files_and_split_chains() {
+        let dir = TempDir::new().unwrap();
+        let ids = commits(dir.path(), HISTORY);
+        let repo = Repository::open(dir.path()).unwrap();
+        let objects = repo.path().join("objects");
+        assert!(CommitGraph::open(&objects).is_none());
+
+        for (layers, corrected) in [
+            (vec![ids.clone()], true),
+            (vec![ids.clone()], false),
+            (
+                vec![ids[..2].to_vec(), ids[2..5].to_vec(), ids[5..].to_vec()],
+                true,
+            ),
+        ] {
+            let _ = std::fs::remove_file(objects.join("info/commit-graph"));
+            let _ = std::fs::remove_dir_all(objects.join("info/commit-graphs"));
+            write_graph(&repo, &layers, corrected);
+            let graph = CommitGraph::open(&objects).unwrap();
+            check_graph(&repo, &graph, &ids);
+            let expected = if corrected { CORRECTED } else { LEVELS };
+            assert_eq!(generations(&graph, &ids), expected);
+        }
+    }
+
+    #[test]
+    fn reads_graphs_written_by_git() {
+        let dir = TempDir::new().unwrap();
+        let ids = commits(dir.path(), HISTORY);
+        let repo = Repository::open(dir.path()).unwrap();
+        let objects = repo.path().join("objects");
+
+        // A base layer up to the octopus merge, and one more on top.
+        let git = |args: &[&str]| git(dir.path(), args);
+        let Some(_) = git(&["update-ref", "refs/heads/main", &ids[4].to_string()]) else {
+            return; // git is not installed
+        };
+        git(&["commit-graph", "write", "--reachable", "--split"]);
+        git(&["update-ref", "refs/heads/main", &ids[7].to_string()]);
+        git(&["commit-graph", "write", "--reachable", "--split=no-merge"]);
+        let chain = std::fs::read_to_string(objects.join("info/commit-graphs/commit-graph-chain"));
+        assert_eq!(chain.unwrap().lines().count(), 2);
+
+        let graph = CommitGraph::open(&objects).unwrap();
+        check_graph(&repo, &graph, &ids);
+        assert_eq!(generations(&graph, &ids), CORRECTED);
+    }
+
+    #[test]
+    fn ignores_malformed_files() {
+        let dir = TempDir::new().unwrap();
+        let ids = commits(dir.path(), &[("root", &[], 1_000)]);
+        let repo = Repository::open(dir.path()).unwrap();
+        write_graph(&repo, &[ids], true);
+        let objects = repo.path().join("objects");
+        let path = objects.join("info/commit-graph");
+        let file = std::fs::read(&path).unwrap();
+
+        std::fs::write(&path, &file[..file.len() / 2]).unwrap();
+        assert!(CommitGraph::open(&objects).is_none());
+        let mut sha256 = file.clone();
+        sha256[5] = 2;
+        std::fs::write(&path, sha256).unwrap();
+        assert!(CommitGraph::open(&objects).is_none());
+    }
+}
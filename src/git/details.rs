@@ -0,0 +1,121 @@
+use super::commit::{Identity, RefKind};
+use super::signature::SignatureCheck;
+use super::trailer::Trailer;
+use super::{Oid, Repo, Result};
+
+/// A tag pointing at a commit. Lightweight tags only have a name.
+pub struct TagDetails {
+    /// Short tag name (e.g. "v1.0").
+    pub name: String,
+    /// Tagger of an annotated tag (may be missing even then).
+    pub tagger: Option<Identity>,
+    /// Tag message without the signature block.
+    pub message: Option<String>,
+    /// Armored PGP / SSH / X.509 signature appended to the message.
+    pub signature: Option<String>,
+}
+
+/// Everything shown in the header of the commit view.
+pub struct CommitDetails {
+    /// Commit hash.
+    pub id: Oid,
+    /// Parent commit IDs.
+    pub parent_ids: Vec<Oid>,
+    /// Who wrote the change, and when.
+    pub author: Identity,
+    /// Who created the commit object, and when.
+    pub committer: Identity,
+    /// Full commit message.
+    pub message: String,
+    /// Result of verifying the commit signature.
+    pub signature: SignatureCheck,
+    /// Trailers of the message.
+    pub trailers: Vec<Trailer>,
+    /// Tags pointing at the commit.
+    pub tags: Vec<TagDetails>,
+}
+
+/// Split a tag message into its text and a trailing signature block.
+fn split_signature(message: &str) -> (&str, Option<&str>) {
+    const MARKERS: [&str; 3] = [
+        "-----BEGIN PGP SIGNATURE-----",
+        "-----BEGIN SSH SIGNATURE-----",
+        "-----BEGIN SIGNED MESSAGE-----",
+    ];
+    let start = MARKERS
+        .iter()
+        .filter_map(|m| message.find(m))
+        .filter(|&i| i == 0 || message[..i].ends_with('\n'))
+        .min();
+    match start {
+        Some(i) => (&message[..i], Some(&message[i..])),
+        None => (message, None),
+    }
+}
+
+impl Repo {
+    /// Header details of the commit `id` and the tags pointing at it.
+    pub fn commit_details(&self, id: Oid) -> Result<CommitDetails> {
+        let commit = self.inner.find_commit(id.git())?;
+        let tags = self
+            .refs_for(id)
+            .iter()
+            .filter_map(|r| match r.kind {
+                RefKind::Tag => Some(TagDetails {
+                    name: r.name.clone(),
+                    tagger: None,
+                    message: None,
+                    signature: None,
+                }),
+                RefKind::AnnotatedTag => {
+                    let tag = self
+                        .inner
+                        .find_reference(&format!("refs/tags/{}", r.name))
+                        .and_then(|reference| reference.peel_to_tag())
+                        .ok()?;
+                    let (message, signature) = split_signature(tag.message().unwrap_or(""));
+                    Some(TagDetails {
+                        name: r.name.clone(),
+                        tagger: tag.tagger().map(|t| self.identity(&t)),
+                        message: Some(message.to_string()),
+                        signature: signature.map(str::to_string),
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+
+        let message = commit.message().unwrap_or("").to_string();
+        Ok(CommitDetails {
+            id,
+            parent_ids: commit.parent_ids().map(Oid::from_git).collect(),
+            author: self.identity(&commit.author()),
+            committer: self.identity(&commit.committer()),
+            signature: self.verify_signature(id),
+            trailers: self.trailers(&message),
+            message,
+            tags,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_trailing_signature() {
+        let message = "Release 1.1\n\nNotes.\n-----BEGIN PGP SIGNATURE-----\n\nabc\n-----END PGP SIGNATURE-----\n";
+        let (text, signature) = split_signature(message);
+        assert_eq!(text, "Release 1.1\n\nNotes.\n");
+        assert!(
+            signature
+                .unwrap()
+                .starts_with("-----BEGIN PGP SIGNATURE-----")
+        );
+
+        let (text, signature) = split_signature("mentions -----BEGIN PGP SIGNATURE----- inline\n");
+        assert_eq!(text, "mentions -----BEGIN PGP SIGNATURE----- inline\n");
+        assert!(signature.is_none());
+    }
+}
@@ -0,0 +1,224 @@
+use git2::{Delta, DiffLineType, FileMode, Patch};
+
+use super::{Oid, Result};
+
+/// How a file changed between the two sides of a diff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileStatus {
+    /// New file (or untracked, in the working tree diff).
+    Added,
+    /// Removed file.
+    Deleted,
+    /// Content changed in place.
+    Modified,
+    /// Moved, possibly with changes.
+    Renamed,
+    /// Copied from another file.
+    Copied,
+    /// Switched between file, symlink and submodule.
+    TypeChange,
+}
+
+impl FileStatus {
+    /// One-letter code as used by `git diff --name-status`.
+    pub fn code(self) -> char {
+        match self {
+            FileStatus::Added => 'A',
+            FileStatus::Deleted => 'D',
+            FileStatus::Modified => 'M',
+            FileStatus::Renamed => 'R',
+            FileStatus::Copied => 'C',
+            FileStatus::TypeChange => 'T',
+        }
+    }
+
+    fn from_delta(delta: Delta) -> Self {
+        match delta {
+            Delta::Added | Delta::Untracked => FileStatus::Added,
+            Delta::Deleted => FileStatus::Deleted,
+            Delta::Renamed => FileStatus::Renamed,
+            Delta::Copied => FileStatus::Copied,
+            Delta::Typechange => FileStatus::TypeChange,
+            _ => FileStatus::Modified,
+        }
+    }
+}
+
+/// Kind of a line in a rendered patch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineKind {
+    /// Hunk header (`@@ -1,2 +1,3 @@`).
+    Hunk,
+    /// Unchanged line around a change.
+    Context,
+    /// Line only on the new side.
+    Added,
+    /// Line only on the old side.
+    Removed,
+    /// Informational line (e.g. "Binary files differ").
+    Meta,
+}
+
+/// A single line of a file's patch.
+#[derive(Clone, Debug)]
+pub struct DiffLine {
+    /// What the line is.
+    pub kind: LineKind,
+    /// Line content without the trailing newline or `+`/`-` origin marker.
+    pub text: String,
+}
+
+/// Changes to one file.
+#[derive(Clone, Debug)]
+pub struct FileDiff {
+    /// Path on the new side (the old side for deletions).
+    pub path: String,
+    /// Previous path for renames and copies.
+    pub old_path: Option<String>,
+    /// How the file changed.
+    pub status: FileStatus,
+    /// Number of added lines.
+    pub additions: usize,
+    /// Number of removed lines.
+    pub deletions: usize,
+    /// Patch lines, hunk headers included.
+    pub lines: Vec<DiffLine>,
+    /// Set when the file is a submodule whose recorded commit changed.
+    pub submodule: Option<SubmoduleChange>,
+}
+
+/// A submodule pointer change, shown like `git diff --submodule=log`.
+#[derive(Clone, Debug)]
+pub struct SubmoduleChange {
+    /// Commit recorded before (`None` if the submodule was added).
+    pub old: Option<Oid>,
+    /// Commit recorded after (`None` if the submodule was removed).
+    pub new: Option<Oid>,
+    /// Commits between the two, newest first: those only `new` reaches,
+    /// then those only `old` reaches. `None` when the submodule is not
+    /// checked out or lacks one of the commits.
+    pub commits: Option<Vec<SubmoduleCommit>>,
+}
+
+/// A commit listed in a [`SubmoduleChange`].
+#[derive(Clone, Debug)]
+pub struct SubmoduleCommit {
+    /// Commit id.
+    pub id: Oid,
+    /// First line of the message.
+    pub summary: String,
+    /// Whether the change adds the commit (reachable from the new pointer
+    /// only) rather than removes it.
+    pub added: bool,
+}
+
+/// A diff split per file.
+#[derive(Clone, Debug, Default)]
+pub struct Diff {
+    /// Changed files, in libgit2's path order.
+    pub files: Vec<FileDiff>,
+}
+
+impl Diff {
+    /// Convert a `git2::Diff` into per-file patches.
+    pub(super) fn from_git(diff: &mut git2::Diff<'_>) -> Result<Self> {
+        diff.find_similar(None)?;
+
+        let mut files = Vec::with_capacity(diff.deltas().len());
+        for idx in 0..diff.deltas().len() {
+            let Some(delta) = diff.get_delta(idx) else {
+                continue;
+            };
+            let new_path = delta
+                .new_file()
+                .path()
+                .map(|p| p.to_string_lossy().into_owned());
+            let old_path = delta
+                .old_file()
+                .path()
+                .map(|p| p.to_string_lossy().into_owned());
+            let status = FileStatus::from_delta(delta.status());
+            let path = new_path
+                .clone()
+                .or_else(|| old_path.clone())
+                .unwrap_or_default();
+            let old_path = match status {
+                FileStatus::Renamed | FileStatus::Copied => old_path,
+                _ => None,
+            };
+
+            let mut file = FileDiff {
+                path,
+                old_path,
+                status,
+                additions: 0,
+                deletions: 0,
+                lines: Vec::new(),
+                submodule: None,
+            };
+
+            // Submodule pointers are listed by commit, once the submodule's
+            // repository is looked at (see `Repo::load_diff`).
+            let gitlink = |f: git2::DiffFile<'_>| {
+                (f.mode() == FileMode::Commit && !f.id().is_zero()).then(|| Oid::from_git(f.id()))
+            };
+            let (old, new) = (gitlink(delta.old_file()), gitlink(delta.new_file()));
+            if old.is_some() || new.is_some() {
+                file.submodule = Some(SubmoduleChange {
+                    old,
+                    new,
+                    commits: None,
+                });
+                files.push(file);
+                continue;
+            }
+
+            match Patch::from_diff(diff, idx)? {
+                Some(patch) if !delta.flags().is_binary() => {
+                    collect_patch_lines(&patch, &mut file)?;
+                }
+                _ => file.lines.push(DiffLine {
+                    kind: LineKind::Meta,
+                    text: "Binary files differ".to_string(),
+                }),
+            }
+            files.push(file);
+        }
+
+        Ok(Self { files })
+    }
+}
+
+/// Append the hunks of `patch` to `file`, counting added and removed lines.
+fn collect_patch_lines(patch: &Patch<'_>, file: &mut FileDiff) -> Result<()> {
+    for h in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(h)?;
+        file.lines.push(DiffLine {
+            kind: LineKind::Hunk,
+            text: String::from_utf8_lossy(hunk.header())
+                .trim_end()
+                .to_string(),
+        });
+        for l in 0..line_count {
+            let line = patch.line_in_hunk(h, l)?;
+            let kind = match line.origin_value() {
+                DiffLineType::Addition => {
+                    file.additions += 1;
+                    LineKind::Added
+                }
+                DiffLineType::Deletion => {
+                    file.deletions += 1;
+                    LineKind::Removed
+                }
+                DiffLineType::Context => LineKind::Context,
+                _ => LineKind::Meta,
+            };
+            let text = String::from_utf8_lossy(line.content());
+            file.lines.push(DiffLine {
+                kind,
+                text: text.trim_end_matches(['\n', '\r']).to_string(),
+            });
+        }
+    }
+    Ok(())
+}
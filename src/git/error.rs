@@ -0,0 +1,107 @@
+use std::fmt;
+
+use super::Oid;
+
+/// Result type of the library.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors returned by [`Repo`](super::Repo) operations.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// An error reported by libgit2. Displays only its message, without the
+    /// class and code suffix.
+    #[error("{0}")]
+    Git(#[source] GitError),
+
+    /// A revspec resolved to an object that is not a commit.
+    #[error("'{0}' does not name a commit")]
+    NotACommit(String),
+
+    /// A reference name is not valid UTF-8.
+    #[error("reference name is not valid UTF-8")]
+    InvalidRefName,
+
+    /// A checkout would overwrite local changes to these paths.
+    #[error("local changes would be overwritten: {}", .0.join(", "))]
+    WouldOverwrite(Vec<String>),
+
+    /// The working tree has uncommitted changes.
+    #[error("the working tree has uncommitted changes; commit or stash them first")]
+    DirtyWorktree,
+
+    /// The index has staged changes.
+    #[error("the index has staged changes; commit or stash them first")]
+    DirtyIndex,
+
+    /// Applying a commit left nothing to commit.
+    #[error("nothing to commit; the changes are already in HEAD")]
+    NothingToCommit,
+
+    /// Reading or writing a file in the git directory failed.
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    /// A rebase plan cannot be carried out.
+    #[error(transparent)]
+    Rebase(#[from] RebaseError),
+
+    /// An error reported by gitoxide.
+    #[cfg(feature = "gix")]
+    #[error("{0}")]
+    Gix(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Why a rebase cannot be planned or started.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum RebaseError {
+    /// The range contains a merge commit.
+    #[error("cannot rebase merge commit {0:.7}")]
+    MergeCommit(Oid),
+
+    /// The base commit is not on HEAD's first-parent chain.
+    #[error("{0:.7} is not an ancestor of HEAD")]
+    NotAncestor(Oid),
+
+    /// The base commit has no parent to rebase onto.
+    #[error("cannot rebase the root commit")]
+    RootCommit,
+
+    /// Every entry of the todo list is a drop.
+    #[error("every commit is dropped")]
+    AllDropped,
+
+    /// The first kept entry is a squash or fixup, with nothing to fold into.
+    #[error("the first commit cannot be a {0}")]
+    NothingToFold(&'static str),
+
+    /// Another rebase, merge or cherry-pick is in progress.
+    #[error("another operation is in progress; finish or abort it first")]
+    InProgress,
+
+    /// The saved state of a rebase holds something reki cannot carry out.
+    #[error("cannot resume the rebase: {0}")]
+    Unsupported(String),
+}
+
+/// A libgit2 error, kept opaque so that the libgit2 version is not part of
+/// the API.
+#[derive(Debug)]
+pub struct GitError(git2::Error);
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.message())
+    }
+}
+
+impl std::error::Error for GitError {}
+
+// Lets `?` wrap libgit2 errors inside the crate; not part of the API.
+#[doc(hidden)]
+impl From<git2::Error> for Error {
+    fn from(e: git2::Error) -> Self {
+        Error::Git(GitError(e))
+    }
+}
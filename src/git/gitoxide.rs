@@ -0,0 +1,377 @@
+//! Commit loading and ref mapping on top of gitoxide, used instead of
+//! libgit2 for [`Backend::Gix`](super::Backend::Gix).
+
+use std::collections::{HashMap, HashSet};
+
+use gix::ObjectId;
+use gix::bstr::{BStr, ByteSlice};
+
+use super::commit::{CommitInfo, Identity, RefDecoration};
+use super::repo::build_ref_map;
+use super::{Error, Oid, Repo, Result};
+
+impl From<gix::open::Error> for Error {
+    fn from(e: gix::open::Error) -> Self {
+        Error::Gix(Box::new(e))
+    }
+}
+
+fn gix_error(e: impl std::error::Error + Send + Sync + 'static) -> Error {
+    Error::Gix(Box::new(e))
+}
+
+fn to_oid(id: gix::ObjectId) -> Oid {
+    Oid::from_bytes(id.as_bytes()).unwrap_or_else(|_| Oid::zero())
+}
+
+fn to_gix(id: Oid) -> gix::ObjectId {
+    gix::ObjectId::from_bytes_or_panic(id.as_bytes())
+}
+
+/// A repository opened with gitoxide, along with what reading commits
+/// needs from the repository as a whole, loaded once rather than per commit.
+pub(super) struct Gix {
+    repo: gix::Repository,
+    /// Boundary commits of a shallow clone.
+    shallow: HashSet<ObjectId>,
+    mailmap: gix::mailmap::Snapshot,
+}
+
+impl Gix {
+    /// Open the repository whose git dir is `git_dir` with gitoxide.
+    pub(super) fn open(git_dir: &std::path::Path) -> Result<Self> {
+        let mut repo = gix::open(git_dir)?;
+        // Commits are read once per row; a small cache covers the parents the
+        // time-ordered walk looks up again.
+        repo.object_cache_size_if_unset(4 * 1024 * 1024);
+        let mut gix = Self {
+            repo,
+            shallow: HashSet::new(),
+            mailmap: gix::mailmap::Snapshot::default(),
+        };
+        gix.refresh()?;
+        Ok(gix)
+    }
+
+    /// Re-read the shallow boundary and `.mailmap`, e.g. after a fetch.
+    pub(super) fn refresh(&mut self) -> Result<()> {
+        let shallow = self.repo.shallow_commits().map_err(gix_error)?;
+        self.shallow = shallow.iter().flat_map(|s| s.iter().copied()).collect();
+        self.mailmap = self.repo.open_mailmap();
+        Ok(())
+    }
+
+    /// Commit time and parents of `oid`.
+    pub(super) fn read_commit(&self, oid: Oid) -> Result<(i64, Vec<Oid>)> {
+        let commit = self.repo.find_commit(to_gix(oid)).map_err(gix_error)?;
+        let time = commit.time().map_err(gix_error)?.seconds;
+        Ok((time, self.parent_ids(&commit)))
+    }
+
+    /// Read the display information of commit `oid`, with the same identity
+    /// mapping and trailer parsing as the libgit2 backend.
+    pub(super) fn commit_info(&self, owner: &Repo, oid: Oid) -> Result<CommitInfo> {
+        let commit = self.repo.find_commit(to_gix(oid)).map_err(gix_error)?;
+        let raw = commit.message_raw().map_err(gix_error)?;
+        let raw = raw.to_str().unwrap_or("");
+        // libgit2 drops the newlines the message starts with.
+        let message = raw.trim_start_matches('\n');
+        Ok(CommitInfo {
+            id: oid,
+            parent_ids: self.parent_ids(&commit),
+            summary: summary(message),
+            author: self.identity(commit.author().map_err(gix_error)?)?,
+            committer: self.identity(commit.committer().map_err(gix_error)?)?,
+            trailers: owner.trailers(message),
+            uncommitted: None,
+        })
+    }
+
+    /// Parents of `commit`; none if it is a boundary commit of a shallow
+    /// clone, as libgit2 grafts them away.
+    fn parent_ids(&self, commit: &gix::Commit<'_>) -> Vec<Oid> {
+        if self.shallow.contains(&commit.id) {
+            return Vec::new();
+        }
+        commit.parent_ids().map(|id| to_oid(id.detach())).collect()
+    }
+
+    /// The identity recorded in `sig`, mapped through `.mailmap`.
+    fn identity(&self, sig: gix::actor::SignatureRef<'_>) -> Result<Identity> {
+        let text = |s: &BStr, default: &str| s.to_str().unwrap_or(default).to_string();
+        let time = sig.time().map_err(gix_error)?.seconds;
+        let (raw_name, raw_email) = (text(sig.name, "unknown"), text(sig.email, ""));
+        let (name, email) = match self.mailmap.try_resolve(sig) {
+            Some(mapped) => (
+                text(mapped.name.as_ref(), "unknown"),
+                text(mapped.email.as_ref(), ""),
+            ),
+            None => (raw_name.clone(), raw_email.clone()),
+        };
+        let changed = name != raw_name || email != raw_email;
+        Ok(Identity {
+            name,
+            email,
+            raw: changed.then_some((raw_name, raw_email)),
+            time,
+        })
+    }
+
+    /// Decorations of every commit, read with gitoxide.
+    pub(super) fn ref_map(&self) -> Result<HashMap<Oid, Vec<RefDecoration>>> {
+        ref_map(&self.repo)
+    }
+}
+
+/// First paragraph of `message` on one line, as `git_commit_summary`
+/// builds it: whitespace runs that contain a newline become one space,
+/// other runs are kept, and trailing whitespace is dropped.
+fn summary(message: &str) -> String {
+    let mut out = String::new();
+    let mut space: Option<(usize, bool)> = None;
+    for (i, ch) in message.char_indices() {
+        if ch == '\n' {
+            // A blank or whitespace-only line ends the paragraph.
+            let rest = &message[i + 1..];
+            let line = rest.split('\n').next().unwrap_or("");
+            if line.trim_matches(is_space_nonlf).is_empty() {
+                break;
+            }
+        }
+        if is_space(ch) {
+            let run = space.get_or_insert((i, false));
+            run.1 |= ch == '\n';
+        } else {
+            if let Some((start, newline)) = space.take() {
+                if newline {
+                    out.push(' ');
+                } else {
+                    out.push_str(&message[start..i]);
+                }
+            }
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// C `isspace`: space, tab, newline, vertical tab, form feed, carriage return.
+fn is_space(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\n' | '\x0b' | '\x0c' | '\r')
+}
+
+fn is_space_nonlf(ch: char) -> bool {
+    ch != '\n' && is_space(ch)
+}
+
+fn ref_map(repo: &gix::Repository) -> Result<HashMap<Oid, Vec<RefDecoration>>> {
+    let head = repo.head_id().ok().map(|id| to_oid(id.detach()));
+
+    let mut refs = Vec::new();
+    let platform = repo.references().map_err(gix_error)?;
+    for reference in platform.all().map_err(gix_error)? {
+        let Ok(mut reference) = reference else {
+            continue;
+        };
+        let Ok(name) = reference.name().as_bstr().to_str().map(str::to_string) else {
+            continue;
+        };
+        let annotated = reference.clone().peel_to_tag().is_ok();
+        let Ok(commit) = reference.peel_to_commit() else {
+            continue;
+        };
+        refs.push((name, to_oid(commit.id), annotated));
+    }
+
+    // Newest stash first, so positions match `stash@{n}`.
+    let mut stash_bases = Vec::new();
+    if let Ok(Some(stash)) = repo.try_find_reference("refs/stash")
+        && let Ok(Some(entries)) = stash.log_iter().rev()
+    {
+        for entry in entries.flatten() {
+            let base = repo
+                .find_commit(entry.new_oid)
+                .ok()
+                .and_then(|c| c.parent_ids().next().map(|id| to_oid(id.detach())));
+            stash_bases.push(base);
+        }
+    }
+
+    Ok(build_ref_map(head, refs, stash_bases))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use git2::{Oid, Repository, Signature, Time};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::git::{Backend, TrailerFilter};
+
+    #[test]
+    fn builds_summaries_like_libgit2() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let sig = Signature::now("A", "a@x.org").unwrap();
+        for message in [
+            "plain",
+            "two\nlines\n\nbody",
+            "  indented  title  \n   continued\t\n \t\nbody",
+            "\n\nleading newlines",
+            "tabs\tinside\r\nand crlf\r\n",
+        ] {
+            let id = repo.commit(None, &sig, &sig, message, &tree, &[]).unwrap();
+            let expected = repo.find_commit(id).unwrap().summary().unwrap().to_string();
+            assert_eq!(
+                summary(message.trim_start_matches('\n')),
+                expected,
+                "{message:?}"
+            );
+        }
+    }
+
+    /// A repository with branches, a merge, both kinds of tags, a remote
+    /// branch, a stash, trailers, a `.mailmap` and two commits made in the
+    /// same second.
+    fn fixture() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let mut repo = Repository::init(dir.path()).unwrap();
+        let commit = |n: i64, message: &str, parents: &[Oid], file: &str| -> Oid {
+            std::fs::write(dir.path().join(file), n.to_string()).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new(file)).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let author =
+                Signature::new("al", "al@old.org", &Time::new(1_700_000_000 + n * 60, 0)).unwrap();
+            let committer =
+                Signature::new("Bo", "bo@x.org", &Time::new(1_700_000_030 + n * 60, 60)).unwrap();
+            let parents: Vec<_> = parents
+                .iter()
+                .map(|id| repo.find_commit(*id).unwrap())
+                .collect();
+            let parents: Vec<_> = parents.iter().collect();
+            repo.commit(None, &author, &committer, message, &tree, &parents)
+                .unwrap()
+        };
+
+        let root = commit(0, "root", &[], "a");
+        let main1 = commit(
+            1,
+            "main one\ncontinued\n\nCo-authored-by: al <al@old.org>\n",
+            &[root],
+            "a",
+        );
+        let feat1 = commit(1, "feat one\n\nReviewed-by: Cy <cy@x.org>\n", &[root], "b");
+        let main2 = commit(3, "main two", &[main1], ".mailmap");
+        let merge = commit(4, "merge feat", &[main2, feat1], "a");
+
+        repo.reference("refs/heads/main", merge, true, "").unwrap();
+        repo.reference("refs/heads/feat", feat1, true, "").unwrap();
+        repo.reference("refs/remotes/origin/main", main2, true, "")
+            .unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+        let target = repo.find_object(main1, None).unwrap();
+        let sig = Signature::new("T", "t@x.org", &Time::new(1_700_001_000, 0)).unwrap();
+        repo.tag("v1", &target, &sig, "first", false).unwrap();
+        repo.tag_lightweight("light", &target, false).unwrap();
+        drop(target);
+
+        std::fs::write(dir.path().join("a"), "dirty").unwrap();
+        repo.stash_save(&sig, "wip", None).unwrap();
+        std::fs::write(
+            dir.path().join(".mailmap"),
+            "Al <al@new.org> <al@old.org>\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    /// The log with the decorations of each commit.
+    fn log(
+        dir: &Path,
+        backend: Backend,
+        range: Option<&str>,
+        filter: Option<&str>,
+    ) -> Vec<(CommitInfo, Vec<RefDecoration>)> {
+        let mut repo = Repo::open_with(dir, backend).unwrap();
+        repo.set_range(range.map(str::to_string)).unwrap();
+        repo.set_filter(filter.and_then(TrailerFilter::parse));
+        repo.reload().unwrap();
+        let mut commits = Vec::new();
+        loop {
+            let batch = repo.load_commits().unwrap();
+            if batch.is_empty() {
+                break commits;
+            }
+            for c in batch {
+                let refs = repo.refs_for(c.id).to_vec();
+                commits.push((c, refs));
+            }
+        }
+    }
+
+    #[test]
+    fn backends_produce_identical_logs() {
+        let dir = fixture();
+        for (range, filter) in [
+            (None, None),
+            (Some("feat..main"), None),
+            (Some("HEAD~1"), None),
+            (None, Some("Reviewed-by")),
+        ] {
+            let git2 = log(dir.path(), Backend::Git2, range, filter);
+            let gix = log(dir.path(), Backend::Gix, range, filter);
+            assert!(!git2.is_empty());
+            assert_eq!(git2, gix, "range {range:?}, filter {filter:?}");
+        }
+
+        let log = log(dir.path(), Backend::Gix, None, None);
+        let names: Vec<_> = log
+            .iter()
+            .flat_map(|(_, refs)| refs)
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "HEAD",
+                "main",
+                "stash@{0}",
+                "origin/main",
+                "feat",
+                "light",
+                "v1",
+            ]
+        );
+        assert_eq!(log[0].0.author.name, "Al");
+    }
+
+    #[test]
+    fn stops_at_the_shallow_boundary() {
+        let dir = fixture();
+        let repo = Repo::open(dir.path()).unwrap();
+        let boundary = [
+            repo.resolve("main~2").unwrap(),
+            repo.resolve("feat").unwrap(),
+        ];
+        let shallow: String = boundary.iter().map(|id| format!("{id}\n")).collect();
+        std::fs::write(dir.path().join(".git/shallow"), shallow).unwrap();
+
+        let git2 = log(dir.path(), Backend::Git2, None, None);
+        let gix = log(dir.path(), Backend::Gix, None, None);
+        assert_eq!(git2, gix);
+        let summaries: Vec<_> = gix.iter().map(|(c, _)| c.summary.as_str()).collect();
+        assert_eq!(
+            summaries,
+            ["merge feat", "main two", "feat one", "main one continued"]
+        );
+    }
+}
@@ -1,15 +1,19 @@
-use git2::Oid;
+use std::fmt;
+
+use super::Oid;
 
 /// ASCII commit-graph lane tracker.
 ///
 /// Call [`Graph::next_row`] for each commit in time order; the returned string
 /// contains `*`, `|` and spaces that the UI can colorize per character.
+#[derive(Default)]
 pub struct Graph {
     /// Active lanes, each heading towards a target OID.
     columns: Vec<Oid>,
 }
 
 impl Graph {
+    /// An empty graph, before the first row.
     pub fn new() -> Self {
         Self {
             columns: Vec::new(),
@@ -18,6 +22,12 @@ impl Graph {
 
     /// Produce the graph string for one commit row (e.g. `"* | "`).
     pub fn next_row(&mut self, id: Oid, parents: &[Oid]) -> String {
+        self.next_node(id, parents).to_string()
+    }
+
+    /// Like [`Graph::next_row`], but return the row as its lanes rather
+    /// than a string, to store it in a few bytes.
+    pub fn next_node(&mut self, id: Oid, parents: &[Oid]) -> GraphRow {
         // Find (or allocate) the column for this commit.
         let my_col = match self.columns.iter().position(|&c| c == id) {
             Some(pos) => pos,
@@ -36,13 +46,10 @@ impl Graph {
             .map(|(i, _)| i)
             .collect();
 
-        // Build node line: `*` at my_col, `|` elsewhere, space-separated.
-        let num_cols = self.columns.len();
-        let mut line = String::with_capacity(num_cols * 2);
-        for i in 0..num_cols {
-            line.push(if i == my_col { '*' } else { '|' });
-            line.push(' ');
-        }
+        let row = GraphRow {
+            column: my_col,
+            lanes: self.columns.len(),
+        };
 
         // Remove converging columns (right-to-left to keep indices stable).
         for &i in converging.iter().rev() {
@@ -71,7 +78,32 @@ impl Graph {
             }
         }
 
-        line
+        row
+    }
+}
+
+/// One row of the graph: `*` in the commit's lane, `|` in the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphRow {
+    /// Lane of the commit.
+    pub column: usize,
+    /// Number of lanes.
+    pub lanes: usize,
+}
+
+impl GraphRow {
+    /// Length of the row as a string.
+    pub fn width(&self) -> usize {
+        self.lanes * 2
+    }
+}
+
+impl fmt::Display for GraphRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in 0..self.lanes {
+            f.write_str(if i == self.column { "* " } else { "| " })?;
+        }
+        Ok(())
     }
 }
 
@@ -102,4 +134,19 @@ mod tests {
         assert_eq!(g.next_row(oid(4), &[oid(5)]), "* | ");
         assert_eq!(g.next_row(oid(5), &[]), "* ");
     }
+
+    #[test]
+    fn rows_draw_their_lanes() {
+        let mut g = Graph::new();
+        g.next_node(oid(1), &[oid(2), oid(3)]);
+        let row = g.next_node(oid(3), &[oid(4)]);
+        assert_eq!(
+            row,
+            GraphRow {
+                column: 1,
+                lanes: 2
+            }
+        );
+        assert_eq!((row.to_string(), row.width()), ("| * ".to_string(), 4));
+    }
 }
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use git2::ObjectType;
+
+use super::commit::{CommitInfo, Identity, RefDecoration, RefKind};
+use super::repo::BATCH_SIZE;
+use super::source::CommitSource;
+use super::{Oid, Result};
+
+/// A commit DAG held in memory, for testing code that consumes a
+/// [`CommitSource`] without a git repository.
+///
+/// Commits are named; [`MemoryRepo::id`] turns a name into the commit's
+/// id. Each commit is a minute newer than the one added before it, and the
+/// log lists them newest first:
+///
+/// ```
+/// use reki::git::{CommitSource, MemoryRepo};
+///
+/// let mut repo = MemoryRepo::new()
+///     .commit("a", &[])
+///     .commit("b", &["a"])
+///     .commit("c", &["a"])
+///     .commit("m", &["b", "c"])
+///     .branch("main", "m")
+///     .head("m");
+/// let log = repo.load_commits().unwrap();
+/// assert_eq!(log[0].id, MemoryRepo::id("m"));
+/// assert_eq!(log[0].parent_ids, [MemoryRepo::id("b"), MemoryRepo::id("c")]);
+/// ```
+pub struct MemoryRepo {
+    /// Oldest first.
+    commits: Vec<MemoryCommit>,
+    refs: HashMap<Oid, Vec<RefDecoration>>,
+    batch_size: usize,
+    /// Number of commits handed out since the last reload.
+    loaded_count: usize,
+}
+
+struct MemoryCommit {
+    id: Oid,
+    parent_ids: Vec<Oid>,
+    name: String,
+}
+
+/// Timestamp of the first commit (2024-01-01 00:00 UTC).
+const EPOCH: i64 = 1_704_067_200;
+
+impl MemoryRepo {
+    /// An empty history, loaded in batches of the same size as [`Repo`](super::Repo).
+    pub fn new() -> Self {
+        Self {
+            commits: Vec::new(),
+            refs: HashMap::new(),
+            batch_size: BATCH_SIZE,
+            loaded_count: 0,
+        }
+    }
+
+    /// Id of the commit called `name`.
+    pub fn id(name: &str) -> Oid {
+        git2::Oid::hash_object(ObjectType::Commit, name.as_bytes())
+            .map_or_else(|_| Oid::zero(), Oid::from_git)
+    }
+
+    /// Add commit `name` (also its summary) with the named parents.
+    pub fn commit(mut self, name: &str, parents: &[&str]) -> Self {
+        self.commits.push(MemoryCommit {
+            id: Self::id(name),
+            parent_ids: parents.iter().map(|p| Self::id(p)).collect(),
+            name: name.to_string(),
+        });
+        self
+    }
+
+    /// Point branch `name` at commit `at`.
+    pub fn branch(self, name: &str, at: &str) -> Self {
+        self.decorate(name, RefKind::Branch, at)
+    }
+
+    /// Put a lightweight tag `name` on commit `at`.
+    pub fn tag(self, name: &str, at: &str) -> Self {
+        self.decorate(name, RefKind::Tag, at)
+    }
+
+    /// Point HEAD at commit `at`.
+    pub fn head(self, at: &str) -> Self {
+        self.decorate("HEAD", RefKind::Head, at)
+    }
+
+    /// Hand out `size` commits per [`CommitSource::load_commits`] call.
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    fn decorate(mut self, name: &str, kind: RefKind, at: &str) -> Self {
+        self.refs
+            .entry(Self::id(at))
+            .or_default()
+            .push(RefDecoration {
+                name: name.to_string(),
+                kind,
+            });
+        self
+    }
+
+    fn commit_info(&self, index: usize, c: &MemoryCommit) -> CommitInfo {
+        let person = Identity {
+            name: "Test".to_string(),
+            email: "test@example.org".to_string(),
+            raw: None,
+            time: EPOCH + index as i64 * 60,
+        };
+        CommitInfo {
+            id: c.id,
+            parent_ids: c.parent_ids.clone(),
+            summary: c.name.clone(),
+            author: person.clone(),
+            committer: person,
+            trailers: Vec::new(),
+            uncommitted: None,
+        }
+    }
+}
+
+impl Default for MemoryRepo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommitSource for MemoryRepo {
+    fn load_commits(&mut self) -> Result<Vec<CommitInfo>> {
+        let batch: Vec<CommitInfo> = self
+            .commits
+            .iter()
+            .enumerate()
+            .rev()
+            .skip(self.loaded_count)
+            .take(self.batch_size)
+            .map(|(i, c)| self.commit_info(i, c))
+            .collect();
+        self.loaded_count += batch.len();
+        Ok(batch)
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        self.loaded_count = 0;
+        Ok(())
+    }
+
+    fn refs_for(&self, oid: Oid) -> &[RefDecoration] {
+        self.refs.get(&oid).map_or(&[], Vec::as_slice)
+    }
+}
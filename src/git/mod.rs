@@ -1,7 +1,39 @@
+mod apply;
+mod checkout;
 mod commit;
+mod commit_graph;
+mod details;
+mod diff;
+mod error;
+#[cfg(feature = "gix")]
+mod gitoxide;
 mod graph;
+mod memory;
+mod oid;
+mod rebase;
+mod reflog;
+mod refs;
 mod repo;
+mod signature;
+mod source;
+mod stash;
+mod submodule;
+mod trailer;
+mod walk;
 
-pub use commit::{CommitInfo, RefKind};
-pub use graph::Graph;
-pub use repo::Repo;
+pub use apply::ApplyOutcome;
+pub use commit::{CommitInfo, Identity, RefDecoration, RefKind, Uncommitted};
+pub use details::{CommitDetails, TagDetails};
+pub use diff::{Diff, DiffLine, FileDiff, FileStatus, LineKind, SubmoduleChange, SubmoduleCommit};
+pub use error::{Error, GitError, RebaseError, Result};
+pub use graph::{Graph, GraphRow};
+pub use memory::MemoryRepo;
+pub use oid::Oid;
+pub use rebase::{RebasePlan, RebaseRun, RebaseStatus, TodoAction, TodoEntry};
+pub use reflog::ReflogEntry;
+pub use repo::{Backend, Repo};
+pub use signature::{SignatureCheck, SignatureStatus};
+pub use source::CommitSource;
+pub use stash::StashEntry;
+pub use trailer::{Trailer, TrailerFilter};
+pub use walk::Order;
@@ -0,0 +1,61 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Error, Result};
+
+/// A commit, tree or blob id: the 20-byte SHA-1 of the object.
+///
+/// Formats as 40 hex digits; a precision shortens it (`{:.7}`).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Oid(git2::Oid);
+
+impl Oid {
+    /// The id made of these 20 bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(Self(git2::Oid::from_bytes(bytes)?))
+    }
+
+    /// The all-zero id git uses for "no object".
+    pub fn zero() -> Self {
+        Self(git2::Oid::zero())
+    }
+
+    /// Whether this is [`Oid::zero`].
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// The raw 20 bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    pub(crate) fn from_git(id: git2::Oid) -> Self {
+        Self(id)
+    }
+
+    pub(crate) fn git(self) -> git2::Oid {
+        self.0
+    }
+}
+
+impl FromStr for Oid {
+    type Err = Error;
+
+    /// Parse a full 40-digit hex id (shorter prefixes are zero-padded).
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Self(git2::Oid::from_str(s)?))
+    }
+}
+
+impl fmt::Display for Oid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Debug for Oid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
@@ -0,0 +1,644 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use git2::{RepositoryState, ResetType, StatusOptions};
+
+use super::{Error, Oid, RebaseError, Repo, Result};
+
+/// What to do with one commit of an interactive rebase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TodoAction {
+    /// Keep the commit as is.
+    Pick,
+    /// Pick with an edited message.
+    Reword,
+    /// Meld into the previous commit, concatenating messages.
+    Squash,
+    /// Meld into the previous commit, keeping its message.
+    Fixup,
+    /// Leave the commit out.
+    Drop,
+}
+
+impl TodoAction {
+    /// Keyword as written in a `git rebase -i` todo list.
+    pub fn name(self) -> &'static str {
+        match self {
+            TodoAction::Pick => "pick",
+            TodoAction::Reword => "reword",
+            TodoAction::Squash => "squash",
+            TodoAction::Fixup => "fixup",
+            TodoAction::Drop => "drop",
+        }
+    }
+}
+
+/// One line of the rebase todo list.
+#[derive(Clone, Debug)]
+pub struct TodoEntry {
+    /// What to do with the commit.
+    pub action: TodoAction,
+    /// Original commit.
+    pub id: Oid,
+    /// First line of the original message.
+    pub summary: String,
+    /// Message the rewritten commit will get (edited by `reword`).
+    pub message: String,
+}
+
+/// Commits to rewrite and the commit they are replayed onto.
+pub struct RebasePlan {
+    /// Parent of the oldest rewritten commit.
+    pub onto: Oid,
+    /// Oldest commit first.
+    pub todo: Vec<TodoEntry>,
+}
+
+/// A rebase in progress.
+///
+/// libgit2's rebase replays a range as picks in its original order, which
+/// leaves no room for reordering, squashing or rewording. Each entry is
+/// applied here with a cherry-pick instead, and the state is written to
+/// `.git/rebase-merge` before every step, in the layout git's own sequencer
+/// uses. A rebase paused when reki exits can be resumed in reki (see
+/// [`Repo::rebase_in_progress`]) or finished with `git rebase --continue`
+/// or `--abort`.
+pub struct RebaseRun {
+    /// Entries to apply, oldest first.
+    pub todo: Vec<TodoEntry>,
+    /// Index of the entry being applied (or the one paused on conflicts).
+    pub next: usize,
+    /// Commit the rewritten history starts from.
+    pub onto: Oid,
+    /// HEAD before the rebase started, restored on abort.
+    orig_head: Oid,
+    /// Branch that HEAD pointed to, moved to the result when done.
+    head_ref: Option<String>,
+}
+
+/// Where a rebase run stopped.
+pub enum RebaseStatus {
+    /// All entries applied; HEAD is at this commit.
+    Done(Oid),
+    /// The entry at `RebaseRun::next` conflicts in these paths.
+    Conflicts(Vec<String>),
+}
+
+impl Repo {
+    /// Plan a rebase of the commits from `base` (inclusive) up to HEAD onto
+    /// the parent of `base`. `base` must be on HEAD's first-parent chain and
+    /// the range must not contain merges.
+    pub fn plan_rebase(&self, base: Oid) -> Result<RebasePlan> {
+        let mut commit = self.inner.head().and_then(|h| h.peel_to_commit())?;
+        let mut todo = Vec::new();
+        loop {
+            let id = Oid::from_git(commit.id());
+            if commit.parent_count() > 1 {
+                return Err(RebaseError::MergeCommit(id).into());
+            }
+            todo.push(TodoEntry {
+                action: TodoAction::Pick,
+                id,
+                summary: commit.summary().unwrap_or("").to_string(),
+                message: commit.message().unwrap_or("").to_string(),
+            });
+            if id == base {
+                break;
+            }
+            commit = match commit.parent(0) {
+                Ok(parent) => parent,
+                Err(_) => return Err(RebaseError::NotAncestor(base).into()),
+            };
+        }
+        let Ok(onto) = commit.parent_id(0) else {
+            return Err(RebaseError::RootCommit.into());
+        };
+        todo.reverse();
+        Ok(RebasePlan {
+            onto: Oid::from_git(onto),
+            todo,
+        })
+    }
+
+    /// Detach HEAD at `plan.onto` and start applying the todo list.
+    /// Refuses to start with uncommitted changes.
+    pub fn start_rebase(&mut self, plan: RebasePlan) -> Result<(RebaseRun, RebaseStatus)> {
+        let RebasePlan { onto, todo } = plan;
+        let Some(first) = todo.iter().find(|e| e.action != TodoAction::Drop) else {
+            return Err(RebaseError::AllDropped.into());
+        };
+        if matches!(first.action, TodoAction::Squash | TodoAction::Fixup) {
+            return Err(RebaseError::NothingToFold(first.action.name()).into());
+        }
+        if self.inner.state() != RepositoryState::Clean {
+            return Err(RebaseError::InProgress.into());
+        }
+        self.ensure_clean_worktree()?;
+
+        let (orig_head, head_ref) = {
+            let head = self.inner.head()?;
+            let id = Oid::from_git(head.peel_to_commit()?.id());
+            let name = head
+                .is_branch()
+                .then(|| head.name().map(str::to_string))
+                .flatten();
+            (id, name)
+        };
+        {
+            let onto = self.inner.find_commit(onto.git())?;
+            self.inner.checkout_tree(onto.as_object(), None)?;
+        }
+        self.inner.set_head_detached(onto.git())?;
+
+        let mut run = RebaseRun {
+            todo,
+            next: 0,
+            onto,
+            orig_head,
+            head_ref,
+        };
+        let status = self.run_rebase(&mut run)?;
+        Ok((run, status))
+    }
+
+    /// The rebase left paused in `.git/rebase-merge`, by reki or by git,
+    /// if there is one.
+    pub fn rebase_in_progress(&self) -> Result<Option<RebaseRun>> {
+        match self.inner.state() {
+            RepositoryState::RebaseInteractive | RepositoryState::RebaseMerge => {
+                RebaseRun::load(&self.inner, &self.rebase_dir()).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Resume after the user resolved and staged the conflicts of the
+    /// current entry.
+    pub fn continue_rebase(&mut self, run: &mut RebaseRun) -> Result<RebaseStatus> {
+        let mut index = self.inner.index()?;
+        index.read(false)?;
+        if index.has_conflicts() {
+            return Ok(RebaseStatus::Conflicts(self.conflicted_paths()?));
+        }
+        self.commit_step(&run.todo[run.next])?;
+        run.next += 1;
+        self.run_rebase(run)
+    }
+
+    /// Throw away the rebase and restore HEAD, index and working tree.
+    pub fn abort_rebase(&mut self, run: &RebaseRun) -> Result<()> {
+        {
+            let orig = self.inner.find_commit(run.orig_head.git())?;
+            self.inner.reset(orig.as_object(), ResetType::Hard, None)?;
+        }
+        match &run.head_ref {
+            Some(name) => self.inner.set_head(name),
+            None => self.inner.set_head_detached(run.orig_head.git()),
+        }?;
+        self.inner.cleanup_state()?;
+        self.refresh_refs()
+    }
+
+    /// Apply entries from `run.next` until done or a conflict.
+    fn run_rebase(&mut self, run: &mut RebaseRun) -> Result<RebaseStatus> {
+        let dir = self.rebase_dir();
+        while let Some(entry) = run.todo.get(run.next) {
+            run.save(&dir)?;
+            if entry.action != TodoAction::Drop {
+                let commit = self.inner.find_commit(entry.id.git())?;
+                self.inner.cherrypick(&commit, None)?;
+                if self.inner.index()?.has_conflicts() {
+                    self.save_stop(&dir, entry)?;
+                    return Ok(RebaseStatus::Conflicts(self.conflicted_paths()?));
+                }
+                self.commit_step(entry)?;
+            }
+            run.next += 1;
+        }
+
+        let head = self.inner.head().and_then(|h| h.peel_to_commit())?.id();
+        if let Some(name) = &run.head_ref {
+            self.inner
+                .reference(name, head, true, "rebase (reki): finish")?;
+            self.inner.set_head(name)?;
+        }
+        fs::remove_dir_all(&dir)?;
+        self.refresh_refs()?;
+        Ok(RebaseStatus::Done(Oid::from_git(head)))
+    }
+
+    /// Where git keeps the state of an interactive rebase.
+    fn rebase_dir(&self) -> PathBuf {
+        self.inner.path().join("rebase-merge")
+    }
+
+    /// Record what `git rebase --continue` needs to commit `entry` once its
+    /// conflicts are resolved: the message and the original author.
+    fn save_stop(&self, dir: &Path, entry: &TodoEntry) -> Result<()> {
+        let message = self.step_message(entry)?;
+        let commit = self.inner.find_commit(entry.id.git())?;
+        let author = commit.author();
+        let quote = |s: &str| format!("'{}'", s.replace('\'', r"'\''"));
+        let when = author.when();
+        let offset = when.offset_minutes();
+        let script = format!(
+            "GIT_AUTHOR_NAME={}\nGIT_AUTHOR_EMAIL={}\nGIT_AUTHOR_DATE={}\n",
+            quote(&String::from_utf8_lossy(author.name_bytes())),
+            quote(&String::from_utf8_lossy(author.email_bytes())),
+            quote(&format!(
+                "@{} {}{:02}{:02}",
+                when.seconds(),
+                if offset < 0 { '-' } else { '+' },
+                offset.abs() / 60,
+                offset.abs() % 60
+            )),
+        );
+        fs::write(dir.join("message"), &message)?;
+        fs::write(dir.join("author-script"), script)?;
+        fs::write(dir.join("stopped-sha"), format!("{}\n", entry.id))?;
+        fs::write(self.inner.path().join("MERGE_MSG"), &message)?;
+        Ok(())
+    }
+
+    /// Message the commit made for `entry` gets.
+    fn step_message(&self, entry: &TodoEntry) -> Result<String> {
+        let head = self.inner.head().and_then(|h| h.peel_to_commit())?;
+        let head_message = head.message().unwrap_or("");
+        Ok(match entry.action {
+            TodoAction::Squash => format!("{}\n\n{}", head_message.trim_end(), entry.message),
+            TodoAction::Fixup => head_message.to_string(),
+            _ => entry.message.clone(),
+        })
+    }
+
+    /// Commit the index for `entry` on top of (or into) HEAD.
+    fn commit_step(&self, entry: &TodoEntry) -> Result<()> {
+        let original = self.inner.find_commit(entry.id.git())?;
+        let mut index = self.inner.index()?;
+        let tree_id = index.write_tree()?;
+        let tree = self.inner.find_tree(tree_id)?;
+        let head = self.inner.head().and_then(|h| h.peel_to_commit())?;
+        let committer = self.inner.signature()?;
+
+        let message = self.step_message(entry)?;
+
+        match entry.action {
+            TodoAction::Squash | TodoAction::Fixup => {
+                head.amend(
+                    Some("HEAD"),
+                    None,
+                    Some(&committer),
+                    None,
+                    Some(&message),
+                    Some(&tree),
+                )?;
+            }
+            // A pick whose changes are already present becomes empty; skip it.
+            _ if tree_id == head.tree_id() => {}
+            _ => {
+                self.inner.commit(
+                    Some("HEAD"),
+                    &original.author(),
+                    &committer,
+                    &message,
+                    &tree,
+                    &[&head],
+                )?;
+            }
+        }
+        // Clear the cherry-pick, but not the rebase state that
+        // `cleanup_state` would remove with it.
+        for file in ["CHERRY_PICK_HEAD", "MERGE_MSG"] {
+            match fs::remove_file(self.inner.path().join(file)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Fail if tracked files have staged or unstaged changes.
+    fn ensure_clean_worktree(&self) -> Result<()> {
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(false).include_ignored(false);
+        let statuses = self.inner.statuses(Some(&mut opts))?;
+        if !statuses.is_empty() {
+            return Err(Error::DirtyWorktree);
+        }
+        Ok(())
+    }
+}
+
+impl RebaseRun {
+    /// Write the state to `dir` as git's sequencer does: the entry being
+    /// applied is the last line of `done`, the rest are in
+    /// `git-rebase-todo`. Reworded messages, which git would ask for again,
+    /// are kept in `reki-message-<id>` files.
+    fn save(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        let split = (self.next + 1).min(self.todo.len());
+        let lines = |entries: &[TodoEntry]| -> String {
+            entries
+                .iter()
+                .map(|e| format!("{} {} {}\n", e.action.name(), e.id, e.summary))
+                .collect()
+        };
+        let head_name = self.head_ref.as_deref().unwrap_or("detached HEAD");
+        let files = [
+            ("head-name", format!("{head_name}\n")),
+            ("onto", format!("{}\n", self.onto)),
+            ("orig-head", format!("{}\n", self.orig_head)),
+            ("interactive", String::new()),
+            ("done", lines(&self.todo[..split])),
+            ("git-rebase-todo", lines(&self.todo[split..])),
+            ("msgnum", format!("{split}\n")),
+            ("end", format!("{}\n", self.todo.len())),
+        ];
+        for (name, content) in files {
+            fs::write(dir.join(name), content)?;
+        }
+        for entry in self.todo.iter().filter(|e| e.action == TodoAction::Reword) {
+            fs::write(
+                dir.join(format!("reki-message-{}", entry.id)),
+                &entry.message,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Read back the state of a rebase from `dir`. Fails on todo commands
+    /// reki does not carry out, such as `exec` or `edit`.
+    fn load(repo: &git2::Repository, dir: &Path) -> Result<Self> {
+        let read = |name: &str| -> Result<String> {
+            Ok(fs::read_to_string(dir.join(name))?.trim_end().to_string())
+        };
+        let oid = |name: &str| -> Result<Oid> {
+            read(name)?
+                .parse::<Oid>()
+                .map_err(|_| RebaseError::Unsupported(format!("{name} is not a commit id")).into())
+        };
+        let head_name = read("head-name")?;
+        let head_ref = head_name.starts_with("refs/").then_some(head_name);
+
+        let done = read("done").unwrap_or_default();
+        let todo_text = read("git-rebase-todo")?;
+        let mut todo = Vec::new();
+        let mut done_count = 0;
+        for (text, is_done) in [(&done, true), (&todo_text, false)] {
+            for line in text.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                todo.push(parse_todo_line(repo, dir, line)?);
+                done_count += usize::from(is_done);
+            }
+        }
+        Ok(RebaseRun {
+            todo,
+            next: done_count.saturating_sub(1),
+            onto: oid("onto")?,
+            orig_head: oid("orig-head")?,
+            head_ref,
+        })
+    }
+}
+
+/// Parse one line of a todo list: a command, a commit id and its summary.
+fn parse_todo_line(repo: &git2::Repository, dir: &Path, line: &str) -> Result<TodoEntry> {
+    let unsupported = || RebaseError::Unsupported(line.to_string());
+    let mut words = line.splitn(3, ' ');
+    let action = match words.next().unwrap_or_default() {
+        "pick" | "p" => TodoAction::Pick,
+        "reword" | "r" => TodoAction::Reword,
+        "squash" | "s" => TodoAction::Squash,
+        "fixup" | "f" => TodoAction::Fixup,
+        "drop" | "d" => TodoAction::Drop,
+        _ => return Err(unsupported().into()),
+    };
+    let id = words.next().unwrap_or_default();
+    let commit = repo
+        .revparse_single(id)
+        .and_then(|o| o.peel_to_commit())
+        .map_err(|_| unsupported())?;
+    let reworded = fs::read_to_string(dir.join(format!("reki-message-{}", commit.id())));
+    Ok(TodoEntry {
+        action,
+        id: Oid::from_git(commit.id()),
+        summary: commit.summary().unwrap_or("").to_string(),
+        message: match reworded {
+            Ok(message) if action == TodoAction::Reword => message,
+            _ => commit.message().unwrap_or("").to_string(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::git::apply::tests::{check_out, commit, repo};
+
+    /// Commits of HEAD's first-parent chain down to `stop`, newest first,
+    /// with their messages.
+    fn history(git: &Repository, stop: Oid) -> Vec<String> {
+        let mut commit = git.head().unwrap().peel_to_commit().unwrap();
+        let mut messages = Vec::new();
+        while commit.id() != stop.git() {
+            messages.push(commit.message().unwrap().to_string());
+            commit = commit.parent(0).unwrap();
+        }
+        messages
+    }
+
+    fn set(plan: &mut RebasePlan, order: &[(usize, TodoAction)]) {
+        let todo = std::mem::take(&mut plan.todo);
+        plan.todo = order
+            .iter()
+            .map(|&(i, action)| TodoEntry {
+                action,
+                ..todo[i].clone()
+            })
+            .collect();
+    }
+
+    /// `base` (a=1), then `one` (a=2), `two` (a=3) and `three` (b), on
+    /// `main`. Picking `two` without `one` conflicts.
+    fn conflicting() -> (TempDir, Repository, [Oid; 4]) {
+        let (dir, git) = repo();
+        let base = commit(&git, "base", &[], &[("a", Some("1\n"))]);
+        let one = commit(&git, "one", &[base], &[("a", Some("2\n"))]);
+        let two = commit(&git, "two", &[one], &[("a", Some("3\n"))]);
+        let three = commit(&git, "three", &[two], &[("b", Some("x\n"))]);
+        check_out(&git, three);
+        (dir, git, [base, one, two, three])
+    }
+
+    #[test]
+    fn reorders_squashes_rewords_and_drops() {
+        let (dir, git) = repo();
+        let base = commit(&git, "base", &[], &[("a", Some("1\n"))]);
+        let c1 = commit(&git, "c1", &[base], &[("b", Some("b\n"))]);
+        let c2 = commit(&git, "c2", &[c1], &[("c", Some("c\n"))]);
+        let c3 = commit(&git, "c3", &[c2], &[("d", Some("d\n"))]);
+        let c4 = commit(&git, "c4", &[c3], &[("a", Some("2\n"))]);
+        check_out(&git, c4);
+        let mut repo = Repo::open(dir.path()).unwrap();
+
+        assert!(matches!(
+            repo.plan_rebase(base),
+            Err(Error::Rebase(RebaseError::RootCommit))
+        ));
+        let mut plan = repo.plan_rebase(c1).unwrap();
+        assert_eq!(plan.onto, base);
+        let ids: Vec<Oid> = plan.todo.iter().map(|e| e.id).collect();
+        assert_eq!(ids, [c1, c2, c3, c4]);
+
+        use TodoAction::*;
+        set(
+            &mut plan,
+            &[(0, Reword), (2, Pick), (3, Squash), (1, Fixup)],
+        );
+        plan.todo[0].message = "c1 reworded\n".to_string();
+        let Ok((_, RebaseStatus::Done(head))) = repo.start_rebase(plan) else {
+            panic!("expected the rebase to finish");
+        };
+
+        assert_eq!(git.head().unwrap().name(), Some("refs/heads/main"));
+        assert_eq!(git.head().unwrap().target(), Some(head.git()));
+        assert_eq!(history(&git, base), ["c3\n\nc4", "c1 reworded\n"]);
+        let tree = git.find_commit(head.git()).unwrap().tree().unwrap();
+        let mut names: Vec<_> = tree.iter().map(|e| e.name().unwrap().to_string()).collect();
+        names.sort();
+        assert_eq!(names, ["a", "b", "c", "d"]);
+        assert_eq!(git.state(), RepositoryState::Clean);
+        assert!(!dir.path().join(".git/rebase-merge").exists());
+
+        let mut plan = repo.plan_rebase(head).unwrap();
+        set(&mut plan, &[(0, Fixup)]);
+        assert!(matches!(
+            repo.start_rebase(plan),
+            Err(Error::Rebase(RebaseError::NothingToFold("fixup")))
+        ));
+    }
+
+    #[test]
+    fn pauses_on_conflicts_and_resumes_from_disk() {
+        let (dir, git, [base, one, two, three]) = conflicting();
+        let mut repo = Repo::open(dir.path()).unwrap();
+        let mut plan = repo.plan_rebase(one).unwrap();
+        set(
+            &mut plan,
+            &[
+                (0, TodoAction::Drop),
+                (1, TodoAction::Pick),
+                (2, TodoAction::Pick),
+            ],
+        );
+        let Ok((_, RebaseStatus::Conflicts(paths))) = repo.start_rebase(plan) else {
+            panic!("expected conflicts");
+        };
+        assert_eq!(paths, ["a"]);
+        assert_eq!(git.state(), RepositoryState::RebaseInteractive);
+        let again = RebasePlan {
+            onto: base,
+            todo: vec![TodoEntry {
+                action: TodoAction::Pick,
+                id: three,
+                summary: "three".to_string(),
+                message: "three".to_string(),
+            }],
+        };
+        assert!(matches!(
+            repo.start_rebase(again),
+            Err(Error::Rebase(RebaseError::InProgress))
+        ));
+
+        // As if reki had been restarted.
+        let mut repo = Repo::open(dir.path()).unwrap();
+        let mut run = repo.rebase_in_progress().unwrap().unwrap();
+        assert_eq!((run.next, run.onto, run.orig_head), (1, base, three));
+        let ids: Vec<Oid> = run.todo.iter().map(|e| e.id).collect();
+        assert_eq!(ids, [one, two, three]);
+
+        let Ok(RebaseStatus::Conflicts(_)) = repo.continue_rebase(&mut run) else {
+            panic!("expected the conflicts to remain");
+        };
+        std::fs::write(dir.path().join("a"), "3\n").unwrap();
+        let mut index = git.index().unwrap();
+        index.read(true).unwrap();
+        index.add_path(std::path::Path::new("a")).unwrap();
+        index.write().unwrap();
+        let Ok(RebaseStatus::Done(_)) = repo.continue_rebase(&mut run) else {
+            panic!("expected the rebase to finish");
+        };
+        assert_eq!(history(&git, base), ["three", "two"]);
+        assert_eq!(git.state(), RepositoryState::Clean);
+        assert!(repo.rebase_in_progress().unwrap().is_none());
+    }
+
+    #[test]
+    fn abort_restores_the_branch() {
+        let (dir, git, [_, one, _, three]) = conflicting();
+        let mut repo = Repo::open(dir.path()).unwrap();
+        let mut plan = repo.plan_rebase(one).unwrap();
+        set(&mut plan, &[(1, TodoAction::Pick), (2, TodoAction::Pick)]);
+        let (run, _) = repo.start_rebase(plan).unwrap();
+
+        repo.abort_rebase(&run).unwrap();
+        assert_eq!(git.head().unwrap().name(), Some("refs/heads/main"));
+        assert_eq!(git.head().unwrap().target(), Some(three.git()));
+        assert_eq!(git.state(), RepositoryState::Clean);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a")).unwrap(),
+            "3\n"
+        );
+    }
+
+    #[test]
+    fn git_can_take_over() {
+        let git_cli = |dir: &TempDir, args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .env("GIT_EDITOR", "true")
+                .output()
+        };
+        let paused = || {
+            let (dir, git, [base, one, _, three]) = conflicting();
+            let mut repo = Repo::open(dir.path()).unwrap();
+            let mut plan = repo.plan_rebase(one).unwrap();
+            set(&mut plan, &[(1, TodoAction::Pick), (2, TodoAction::Pick)]);
+            repo.start_rebase(plan).unwrap();
+            (dir, git, base, three)
+        };
+
+        let (dir, git, _, three) = paused();
+        let Ok(status) = git_cli(&dir, &["status"]) else {
+            return; // git is not installed
+        };
+        let status = String::from_utf8_lossy(&status.stdout);
+        assert!(
+            status.contains("interactive rebase in progress"),
+            "{status}"
+        );
+        let abort = git_cli(&dir, &["rebase", "--abort"]).unwrap();
+        assert!(abort.status.success(), "{abort:?}");
+        assert_eq!(git.head().unwrap().name(), Some("refs/heads/main"));
+        assert_eq!(git.head().unwrap().target(), Some(three.git()));
+        assert_eq!(git.state(), RepositoryState::Clean);
+
+        let (dir, git, base, _) = paused();
+        std::fs::write(dir.path().join("a"), "3\n").unwrap();
+        git_cli(&dir, &["add", "a"]).unwrap();
+        let done = git_cli(&dir, &["rebase", "--continue"]).unwrap();
+        assert!(done.status.success(), "{done:?}");
+        assert_eq!(git.head().unwrap().name(), Some("refs/heads/main"));
+        assert_eq!(history(&git, base), ["three", "two\n"]);
+        let head = git.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent(0).unwrap().author().name(), Some("T"));
+    }
+}
@@ -0,0 +1,49 @@
+use super::{Oid, Repo, Result};
+
+/// One entry of a reference's reflog.
+pub struct ReflogEntry {
+    /// Value of the reference before the change (zero when it was created).
+    pub old_id: Oid,
+    /// Value of the reference after the change.
+    pub new_id: Oid,
+    /// Action message (e.g. "commit: fix typo", "checkout: moving from a to b").
+    pub message: String,
+    /// Time of the change as a Unix timestamp.
+    pub time: i64,
+}
+
+impl Repo {
+    /// Read the reflog of `name` (`HEAD`, a full ref name or a short one
+    /// like `main`), newest entry first. Returns the full ref name as well.
+    pub fn reflog(&self, name: &str) -> Result<(String, Vec<ReflogEntry>)> {
+        let full_name = if name == "HEAD" {
+            name.to_string()
+        } else {
+            self.inner
+                .resolve_reference_from_short_name(name)?
+                .name()
+                .unwrap_or(name)
+                .to_string()
+        };
+        let reflog = self.inner.reflog(&full_name)?;
+        let entries = reflog
+            .iter()
+            .map(|e| ReflogEntry {
+                old_id: Oid::from_git(e.id_old()),
+                new_id: Oid::from_git(e.id_new()),
+                message: e.message().unwrap_or("").to_string(),
+                time: e.committer().when().seconds(),
+            })
+            .collect();
+        Ok((full_name, entries))
+    }
+
+    /// Whether `oid` is HEAD or one of its ancestors.
+    pub fn reachable_from_head(&self, oid: Oid) -> bool {
+        let Some(head) = self.inner.head().ok().and_then(|h| h.target()) else {
+            return false;
+        };
+        let oid = oid.git();
+        head == oid || self.inner.graph_descendant_of(head, oid).unwrap_or(false)
+    }
+}
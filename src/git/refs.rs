@@ -0,0 +1,63 @@
+use git2::BranchType;
+
+use super::{Oid, Repo, Result};
+
+/// Branch and tag edits. Each one rebuilds the ref map on success so that
+/// decorations reflect the change immediately.
+impl Repo {
+    /// Create a local branch `name` pointing at `target`.
+    pub fn create_branch(&mut self, name: &str, target: Oid) -> Result<()> {
+        {
+            let commit = self.inner.find_commit(target.git())?;
+            self.inner.branch(name, &commit, false)?;
+        }
+        self.refresh_refs()
+    }
+
+    /// Create a tag `name` at `target`; annotated when `message` is given.
+    pub fn create_tag(&mut self, name: &str, target: Oid, message: Option<&str>) -> Result<()> {
+        {
+            let object = self.inner.find_object(target.git(), None)?;
+            match message {
+                Some(message) => {
+                    let tagger = self.inner.signature()?;
+                    self.inner.tag(name, &object, &tagger, message, false)?;
+                }
+                None => {
+                    self.inner.tag_lightweight(name, &object, false)?;
+                }
+            }
+        }
+        self.refresh_refs()
+    }
+
+    /// Rename the local branch `from` to `to`.
+    pub fn rename_branch(&mut self, from: &str, to: &str) -> Result<()> {
+        self.inner
+            .find_branch(from, BranchType::Local)
+            .and_then(|mut branch| branch.rename(to, false).map(drop))?;
+        self.refresh_refs()
+    }
+
+    /// Delete the local branch `name`.
+    pub fn delete_branch(&mut self, name: &str) -> Result<()> {
+        self.inner
+            .find_branch(name, BranchType::Local)
+            .and_then(|mut branch| branch.delete())?;
+        self.refresh_refs()
+    }
+
+    /// Names of all local branches, sorted.
+    pub fn local_branches(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .inner
+            .branches(Some(BranchType::Local))
+            .into_iter()
+            .flatten()
+            .filter_map(|b| b.ok())
+            .filter_map(|(b, _)| b.name().ok().flatten().map(str::to_string))
+            .collect();
+        names.sort();
+        names
+    }
+}
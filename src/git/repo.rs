@@ -1,49 +1,186 @@
 use std::collections::HashMap;
 
-use anyhow::Result;
-use git2::Sort;
-
-use super::commit::{CommitInfo, RefDecoration, RefKind};
+use super::commit::{CommitInfo, Identity, RefDecoration, RefKind, Uncommitted};
+use super::commit_graph::{CommitGraph, Stamp};
+use super::diff::Diff;
+use super::trailer::{Trailer, TrailerFilter, canonicalize_trailers, parse_trailers};
+use super::walk::{History, Order, Walk};
+use super::{Error, Oid, Result};
 
 /// Default batch size for incremental commit loading.
-const BATCH_SIZE: usize = 200;
+pub(super) const BATCH_SIZE: usize = 200;
+
+/// Library used to walk history and read references.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// libgit2, through the `git2` crate.
+    #[default]
+    Git2,
+    /// gitoxide, through the `gix` crate. Everything other than commit
+    /// loading and ref mapping still goes through libgit2.
+    #[cfg(feature = "gix")]
+    Gix,
+}
 
 /// Wrapper around a git2 repository.
 pub struct Repo {
-    inner: git2::Repository,
+    pub(super) inner: git2::Repository,
+    /// Set when commits and refs are read with [`Backend::Gix`].
+    #[cfg(feature = "gix")]
+    gix: Option<super::gitoxide::Gix>,
     /// Mapping from commit Oid to its reference decorations.
-    ref_map: HashMap<git2::Oid, Vec<RefDecoration>>,
-    /// Number of commits already yielded (to resume revwalk without re-skipping).
-    loaded_count: usize,
+    ref_map: HashMap<Oid, Vec<RefDecoration>>,
+    /// The walk [`Repo::load_commits`] resumes, started on first use.
+    walk: Option<Walk>,
+    /// Parents, times and generations of most commits, if git wrote them.
+    graph: Option<CommitGraph>,
+    /// The files `graph` was read from, so a reload only re-reads it when
+    /// git has rewritten it.
+    graph_stamp: Option<Stamp>,
+    order: Order,
+    /// Revision range walked instead of HEAD (e.g. `main..feat`, `v1.0`).
+    range: Option<String>,
+    /// Only commits matching this filter are yielded.
+    filter: Option<TrailerFilter>,
+    /// Extra commit walked alongside HEAD (e.g. opened from the reflog).
+    extra_tip: Option<Oid>,
+    /// `.mailmap` entries (from the worktree, HEAD and `mailmap.file`).
+    mailmap: Option<git2::Mailmap>,
 }
 
 impl Repo {
     /// Open a git repository at the given path (or discover from it).
     pub fn open(path: &std::path::Path) -> Result<Self> {
+        Self::open_with(path, Backend::default())
+    }
+
+    /// Like [`Repo::open`], loading commits and refs with `backend`.
+    pub fn open_with(path: &std::path::Path, backend: Backend) -> Result<Self> {
+        #[cfg(not(feature = "gix"))]
+        let Backend::Git2 = backend;
         let inner = git2::Repository::discover(path)?;
-        let ref_map = Self::build_ref_map(&inner)?;
-        Ok(Self {
+        let mailmap = inner.mailmap().ok();
+        let mut repo = Self {
+            #[cfg(feature = "gix")]
+            gix: match backend {
+                Backend::Gix => Some(super::gitoxide::Gix::open(inner.path())?),
+                Backend::Git2 => None,
+            },
             inner,
-            ref_map,
-            loaded_count: 0,
-        })
+            ref_map: HashMap::new(),
+            walk: None,
+            graph: None,
+            graph_stamp: None,
+            order: Order::default(),
+            range: None,
+            filter: None,
+            extra_tip: None,
+            mailmap,
+        };
+        repo.refresh_commit_graph();
+        repo.refresh_refs()?;
+        Ok(repo)
+    }
+
+    /// The backend commits and refs are loaded with.
+    pub fn backend(&self) -> Backend {
+        #[cfg(feature = "gix")]
+        if self.gix.is_some() {
+            return Backend::Gix;
+        }
+        Backend::Git2
     }
 
-    /// Rebuild the ref decoration map by iterating all references.
-    fn build_ref_map(repo: &git2::Repository) -> Result<HashMap<git2::Oid, Vec<RefDecoration>>> {
-        let mut map: HashMap<git2::Oid, Vec<RefDecoration>> = HashMap::new();
+    /// Re-read all references, e.g. after a branch or tag was changed.
+    pub fn refresh_refs(&mut self) -> Result<()> {
+        #[cfg(feature = "gix")]
+        if let Some(gix) = &self.gix {
+            self.ref_map = gix.ref_map()?;
+            return Ok(());
+        }
+        self.ref_map = Self::git2_ref_map(&mut self.inner)?;
+        Ok(())
+    }
 
-        // Mark HEAD.
-        if let Ok(head) = repo.head()
-            && let Some(oid) = head.target()
-        {
-            map.entry(oid).or_default().push(RefDecoration {
-                name: "HEAD".to_string(),
-                kind: RefKind::Head,
-            });
+    /// Re-read references and restart the commit walk from the beginning,
+    /// e.g. after HEAD moved.
+    pub fn reload(&mut self) -> Result<()> {
+        self.walk = None;
+        self.refresh_commit_graph();
+        self.mailmap = self.inner.mailmap().ok();
+        #[cfg(feature = "gix")]
+        if let Some(gix) = &mut self.gix {
+            gix.refresh()?;
         }
+        self.refresh_refs()
+    }
 
-        // Iterate all references.
+    /// Restrict the log to commits matching `filter` (or lift the
+    /// restriction). Takes effect on the next [`Repo::reload`].
+    pub fn set_filter(&mut self, filter: Option<TrailerFilter>) {
+        self.filter = filter;
+    }
+
+    /// List commits in `order`. Takes effect on the next [`Repo::reload`].
+    pub fn set_order(&mut self, order: Order) {
+        self.order = order;
+    }
+
+    /// The order set with [`Repo::set_order`].
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    /// The filter set with [`Repo::set_filter`].
+    pub fn filter(&self) -> Option<&TrailerFilter> {
+        self.filter.as_ref()
+    }
+
+    /// Walk `range` (`A..B` or a single revision) instead of HEAD. Fails if
+    /// the range does not resolve. Takes effect on the next [`Repo::reload`].
+    pub fn set_range(&mut self, range: Option<String>) -> Result<()> {
+        let old = std::mem::replace(&mut self.range, range);
+        let valid = self.tips().map(drop);
+        if valid.is_err() {
+            self.range = old;
+        }
+        valid
+    }
+
+    /// The range set with [`Repo::set_range`].
+    pub fn range(&self) -> Option<&str> {
+        self.range.as_deref()
+    }
+
+    /// The repository's git dir and common dir (the same unless this is a
+    /// linked worktree).
+    pub fn git_dirs(&self) -> (&std::path::Path, &std::path::Path) {
+        (self.inner.path(), self.inner.commondir())
+    }
+
+    /// Include history reachable from `oid` in the walk, even when it is
+    /// not reachable from HEAD, in place of any earlier extra tip; `None`
+    /// drops it. Returns whether the tip changed. Takes effect on the next
+    /// [`Repo::reload`].
+    pub fn set_extra_tip(&mut self, oid: Option<Oid>) -> bool {
+        std::mem::replace(&mut self.extra_tip, oid) != oid
+    }
+
+    /// Reference decorations pointing at `oid`, from the map shared by
+    /// all commits.
+    pub fn refs_for(&self, oid: Oid) -> &[RefDecoration] {
+        self.ref_map.get(&oid).map_or(&[], Vec::as_slice)
+    }
+
+    /// Read HEAD, all references and the stash with libgit2.
+    fn git2_ref_map(repo: &mut git2::Repository) -> Result<HashMap<Oid, Vec<RefDecoration>>> {
+        let head = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .map(Oid::from_git);
+
+        let mut refs = Vec::new();
         for reference in repo.references()? {
             let reference = match reference {
                 Ok(r) => r,
@@ -57,60 +194,408 @@ impl Repo {
 
             // Resolve to the target commit oid (peel tags).
             let oid = match reference.peel_to_commit() {
-                Ok(commit) => commit.id(),
+                Ok(commit) => Oid::from_git(commit.id()),
                 Err(_) => continue,
             };
+            let annotated = reference.peel_to_tag().is_ok();
+            refs.push((fullname, oid, annotated));
+        }
+
+        // Stash entries decorate the commit they were made on.
+        let mut stashes = Vec::new();
+        repo.stash_foreach(|_, _, id| {
+            stashes.push(*id);
+            true
+        })?;
+        let stash_bases = stashes
+            .into_iter()
+            .map(|id| {
+                repo.find_commit(id)
+                    .and_then(|c| c.parent_id(0))
+                    .ok()
+                    .map(Oid::from_git)
+            })
+            .collect();
+
+        Ok(build_ref_map(head, refs, stash_bases))
+    }
+
+    /// Resolve a revspec (e.g. `abc123`, `v1.2^2`, `origin/main~10`) to a commit.
+    pub fn resolve(&self, spec: &str) -> Result<Oid> {
+        let object = self.inner.revparse_single(spec)?;
+        let commit = object
+            .peel_to_commit()
+            .map_err(|_| Error::NotACommit(spec.to_string()))?;
+        Ok(Oid::from_git(commit.id()))
+    }
+
+    /// The identity recorded in `sig`, mapped through `.mailmap`.
+    pub(super) fn identity(&self, sig: &git2::Signature<'_>) -> Identity {
+        let name = |s: &git2::Signature| s.name().unwrap_or("unknown").to_string();
+        let email = |s: &git2::Signature| s.email().unwrap_or("").to_string();
+        let (raw_name, raw_email) = (name(sig), email(sig));
+        let canonical = self
+            .mailmap
+            .as_ref()
+            .and_then(|m| m.resolve_signature(sig).ok());
+        let (mapped_name, mapped_email) = match &canonical {
+            Some(c) => (name(c), email(c)),
+            None => (raw_name.clone(), raw_email.clone()),
+        };
+        let changed = mapped_name != raw_name || mapped_email != raw_email;
+        Identity {
+            name: mapped_name,
+            email: mapped_email,
+            raw: changed.then_some((raw_name, raw_email)),
+            time: sig.when().seconds(),
+        }
+    }
+
+    /// Trailers of `message`, with identities mapped through `.mailmap`.
+    pub(super) fn trailers(&self, message: &str) -> Vec<Trailer> {
+        let mut trailers = parse_trailers(message);
+        if let Some(mailmap) = &self.mailmap {
+            canonicalize_trailers(&mut trailers, mailmap);
+        }
+        trailers
+    }
+
+    /// Sorted, de-duplicated display names of all decorated refs.
+    pub fn ref_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .ref_map
+            .values()
+            .flatten()
+            .map(|r| r.name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Diff the trees of two commits.
+    pub fn diff_commits(&self, from: Oid, to: Oid) -> Result<Diff> {
+        let old_tree = self.inner.find_commit(from.git())?.tree()?;
+        let new_tree = self.inner.find_commit(to.git())?.tree()?;
+        let mut diff = self
+            .inner
+            .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?;
+        self.load_diff(&mut diff)
+    }
+
+    /// Pseudo-commit rows for staged and unstaged changes, newest first,
+    /// drawn as children of HEAD. Empty when the working tree is clean.
+    pub fn uncommitted_rows(&self) -> Vec<CommitInfo> {
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(true).include_ignored(false);
+        let Ok(statuses) = self.inner.statuses(Some(&mut opts)) else {
+            return Vec::new();
+        };
 
-            let (name, kind) = if let Some(branch) = fullname.strip_prefix("refs/heads/") {
-                (branch.to_string(), RefKind::Branch)
-            } else if let Some(remote) = fullname.strip_prefix("refs/remotes/") {
-                (remote.to_string(), RefKind::Remote)
-            } else if let Some(tag) = fullname.strip_prefix("refs/tags/") {
-                (tag.to_string(), RefKind::Tag)
+        let staged_mask = git2::Status::INDEX_NEW
+            | git2::Status::INDEX_MODIFIED
+            | git2::Status::INDEX_DELETED
+            | git2::Status::INDEX_RENAMED
+            | git2::Status::INDEX_TYPECHANGE;
+        let unstaged_mask = git2::Status::WT_NEW
+            | git2::Status::WT_MODIFIED
+            | git2::Status::WT_DELETED
+            | git2::Status::WT_RENAMED
+            | git2::Status::WT_TYPECHANGE
+            | git2::Status::CONFLICTED;
+        let count = |mask| {
+            statuses
+                .iter()
+                .filter(|s| s.status().intersects(mask))
+                .count()
+        };
+        let staged = count(staged_mask);
+        let unstaged = count(unstaged_mask);
+
+        let head: Vec<Oid> = self
+            .inner
+            .head()
+            .ok()
+            .and_then(|h| h.target())
+            .map(Oid::from_git)
+            .into_iter()
+            .collect();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        let nobody = Identity {
+            name: String::from("Uncommitted"),
+            email: String::new(),
+            raw: None,
+            time: now,
+        };
+        let row = |kind: Uncommitted, files: usize, parent_ids: Vec<Oid>| CommitInfo {
+            id: kind.id(),
+            parent_ids,
+            summary: format!(
+                "{} changes ({files} file{})",
+                match kind {
+                    Uncommitted::Staged => "Staged",
+                    Uncommitted::Unstaged => "Unstaged",
+                },
+                if files == 1 { "" } else { "s" }
+            ),
+            author: nobody.clone(),
+            committer: nobody.clone(),
+            trailers: Vec::new(),
+            uncommitted: Some(kind),
+        };
+
+        let mut rows = Vec::new();
+        if unstaged > 0 {
+            let parent = if staged > 0 {
+                vec![Uncommitted::Staged.id()]
             } else {
-                continue;
+                head.clone()
             };
-
-            map.entry(oid)
-                .or_default()
-                .push(RefDecoration { name, kind });
+            rows.push(row(Uncommitted::Unstaged, unstaged, parent));
+        }
+        if staged > 0 {
+            rows.push(row(Uncommitted::Staged, staged, head));
         }
+        rows
+    }
 
-        Ok(map)
+    /// Diff of the index against HEAD (staged changes).
+    pub fn diff_staged(&self) -> Result<Diff> {
+        let head_tree = self.inner.head().and_then(|h| h.peel_to_tree()).ok();
+        let mut diff = self
+            .inner
+            .diff_tree_to_index(head_tree.as_ref(), None, None)?;
+        self.load_diff(&mut diff)
+    }
+
+    /// Diff of the working tree against the index, including untracked files.
+    pub fn diff_unstaged(&self) -> Result<Diff> {
+        let mut opts = git2::DiffOptions::new();
+        opts.include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+        let mut diff = self.inner.diff_index_to_workdir(None, Some(&mut opts))?;
+        self.load_diff(&mut diff)
+    }
+
+    /// Diff a commit against its first parent (or the empty tree for a root).
+    pub fn diff_commit(&self, id: Oid) -> Result<Diff> {
+        let commit = self.inner.find_commit(id.git())?;
+        let parent_tree = commit.parent(0).and_then(|p| p.tree()).ok();
+        let tree = commit.tree()?;
+        let mut diff = self
+            .inner
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        self.load_diff(&mut diff)
     }
 
     /// Load the next batch of commits incrementally.
     /// Returns up to `BATCH_SIZE` commits starting from where the last call left off.
     pub fn load_commits(&mut self) -> Result<Vec<CommitInfo>> {
-        let mut revwalk = self.inner.revwalk()?;
-        revwalk.push_head()?;
-        revwalk.set_sorting(Sort::TIME)?;
-
-        let commits: Vec<CommitInfo> = revwalk
-            .skip(self.loaded_count)
-            .take(BATCH_SIZE)
-            .filter_map(|oid| oid.ok())
-            .filter_map(|oid| {
-                let commit = self.inner.find_commit(oid).ok()?;
-                let parent_ids: Vec<git2::Oid> = commit.parent_ids().collect();
-                let refs = self
-                    .ref_map
-                    .get(&oid)
-                    .map(|v| v.as_slice())
-                    .unwrap_or_default()
-                    .to_vec();
-                Some(CommitInfo {
-                    id: oid,
-                    parent_ids,
-                    summary: commit.summary().unwrap_or("").to_string(),
-                    author: commit.author().name().unwrap_or("unknown").to_string(),
-                    time: commit.time().seconds(),
-                    refs,
-                })
-            })
-            .collect();
+        let mut walk = match self.walk.take() {
+            Some(walk) => walk,
+            None => Walk::new(self.order, &self.tips()?, &self.history())?,
+        };
 
-        self.loaded_count += commits.len();
-        Ok(commits)
+        // Walk until a full batch matches the filter or history runs out.
+        // Only commits that make it into the batch are read in full.
+        let mut commits = Vec::new();
+        let history = self.history();
+        let walked = loop {
+            let oid = match walk.next(&history) {
+                Ok(Some(oid)) => oid,
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            };
+            let info = match self.commit_info(oid) {
+                Ok(info) => info,
+                Err(e) => break Err(e),
+            };
+            if !self.matches_filter(&info) {
+                continue;
+            }
+            commits.push(info);
+            if commits.len() == BATCH_SIZE {
+                break Ok(());
+            }
+        };
+
+        self.walk = Some(walk);
+        walked.map(|()| commits)
+    }
+
+    /// Every commit of the log (range, extra tips and filter applied),
+    /// newest first, read lazily. The walk ends after the first error.
+    pub fn walk(&self) -> Result<impl Iterator<Item = Result<CommitInfo>> + '_> {
+        let history = self.history();
+        let mut walk = Some(Walk::new(self.order, &self.tips()?, &history)?);
+        Ok(std::iter::from_fn(move || {
+            loop {
+                let step = walk
+                    .as_mut()?
+                    .next(&history)
+                    .and_then(|oid| oid.map(|oid| self.commit_info(oid)).transpose());
+                match step {
+                    Ok(Some(info)) if !self.matches_filter(&info) => continue,
+                    Ok(step) => return step.map(Ok),
+                    Err(e) => {
+                        walk = None;
+                        return Some(Err(e));
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Full message of commit `oid`.
+    pub fn message(&self, oid: Oid) -> Option<String> {
+        let commit = self.inner.find_commit(oid.git()).ok()?;
+        commit.message().map(str::to_string)
     }
+
+    /// The `(commit, hidden)` tips of the walk: the range (or HEAD), then
+    /// the extra tips. Ranges resolve as in `git log`.
+    fn tips(&self) -> Result<Vec<(Oid, bool)>> {
+        let peel =
+            |object: &git2::Object<'_>| object.peel_to_commit().map(|c| Oid::from_git(c.id()));
+        let mut tips = match &self.range {
+            Some(range) if range.contains("..") => {
+                let spec = self.inner.revparse(range)?;
+                if spec.mode().contains(git2::RevparseMode::MERGE_BASE) {
+                    return Err(
+                        git2::Error::from_str("symmetric differences are not supported").into(),
+                    );
+                }
+                let (Some(from), Some(to)) = (spec.from(), spec.to()) else {
+                    return Err(git2::Error::from_str("invalid revspec: range not provided").into());
+                };
+                vec![(peel(from)?, true), (peel(to)?, false)]
+            }
+            Some(rev) => vec![(self.resolve(rev)?, false)],
+            None => vec![(
+                Oid::from_git(self.inner.head()?.peel_to_commit()?.id()),
+                false,
+            )],
+        };
+        tips.extend(self.extra_tip.map(|tip| (tip, false)));
+        Ok(tips)
+    }
+
+    fn history(&self) -> History<'_> {
+        History {
+            repo: self,
+            graph: self.graph.as_ref(),
+        }
+    }
+
+    /// Read the commit graph, unless `core.commitGraph` turns it off or it
+    /// is unchanged since the last read.
+    fn refresh_commit_graph(&mut self) {
+        let enabled = self
+            .inner
+            .config()
+            .and_then(|c| c.get_bool("core.commitGraph"));
+        if enabled == Ok(false) {
+            self.graph = None;
+            self.graph_stamp = None;
+            return;
+        }
+        let objects = self.inner.commondir().join("objects");
+        let stamp = CommitGraph::stamp(&objects);
+        if stamp != self.graph_stamp {
+            self.graph = CommitGraph::open(&objects);
+            self.graph_stamp = stamp;
+        }
+    }
+
+    /// Commit time and parents of `oid`, read from the object database.
+    pub(super) fn read_commit(&self, oid: Oid) -> Result<(i64, Vec<Oid>)> {
+        #[cfg(feature = "gix")]
+        if let Some(gix) = &self.gix {
+            return gix.read_commit(oid);
+        }
+        let commit = self.inner.find_commit(oid.git())?;
+        let parents = commit.parent_ids().map(Oid::from_git).collect();
+        Ok((commit.time().seconds(), parents))
+    }
+
+    fn matches_filter(&self, info: &CommitInfo) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|f| f.matches(&info.trailers))
+    }
+
+    /// Read the display information of one commit.
+    fn commit_info(&self, oid: Oid) -> Result<CommitInfo> {
+        #[cfg(feature = "gix")]
+        if let Some(gix) = &self.gix {
+            return gix.commit_info(self, oid);
+        }
+        let commit = self.inner.find_commit(oid.git())?;
+        Ok(CommitInfo {
+            id: oid,
+            parent_ids: commit.parent_ids().map(Oid::from_git).collect(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            author: self.identity(&commit.author()),
+            committer: self.identity(&commit.committer()),
+            trailers: self.trailers(commit.message().unwrap_or("")),
+            uncommitted: None,
+        })
+    }
+}
+
+/// Build the ref decoration map from HEAD, `(full name, commit, annotated)`
+/// references and the base commit of each stash entry, newest first.
+///
+/// Both backends go through here, so decorations come out in the same
+/// order: HEAD, then references by full name, then stash entries.
+pub(super) fn build_ref_map(
+    head: Option<Oid>,
+    mut refs: Vec<(String, Oid, bool)>,
+    stash_bases: Vec<Option<Oid>>,
+) -> HashMap<Oid, Vec<RefDecoration>> {
+    let mut map: HashMap<Oid, Vec<RefDecoration>> = HashMap::new();
+
+    // Mark HEAD.
+    if let Some(oid) = head {
+        map.entry(oid).or_default().push(RefDecoration {
+            name: "HEAD".to_string(),
+            kind: RefKind::Head,
+        });
+    }
+
+    refs.sort();
+    for (fullname, oid, annotated) in refs {
+        let (name, kind) = if let Some(branch) = fullname.strip_prefix("refs/heads/") {
+            (branch.to_string(), RefKind::Branch)
+        } else if let Some(remote) = fullname.strip_prefix("refs/remotes/") {
+            (remote.to_string(), RefKind::Remote)
+        } else if let Some(tag) = fullname.strip_prefix("refs/tags/") {
+            let kind = if annotated {
+                RefKind::AnnotatedTag
+            } else {
+                RefKind::Tag
+            };
+            (tag.to_string(), kind)
+        } else {
+            continue;
+        };
+
+        map.entry(oid)
+            .or_default()
+            .push(RefDecoration { name, kind });
+    }
+
+    for (index, base) in stash_bases.into_iter().enumerate() {
+        if let Some(base) = base {
+            map.entry(base).or_default().push(RefDecoration {
+                name: format!("stash@{{{index}}}"),
+                kind: RefKind::Stash,
+            });
+        }
+    }
+
+    map
 }
@@ -0,0 +1,299 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use tempfile::NamedTempFile;
+
+use super::{Oid, Repo};
+
+/// Result of verifying a commit's signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// Valid signature by a fully or ultimately trusted GPG key, or by an
+    /// SSH key listed in the allowed signers.
+    Good,
+    /// Valid signature by a GPG key that is not (fully) trusted; like `%G?`
+    /// reporting `U`.
+    Untrusted,
+    /// Signature does not match, or the key is expired or revoked.
+    Bad,
+    /// Signed, but the key is not in the keyring or allowed signers.
+    UnknownKey,
+    /// No signature.
+    Unsigned,
+}
+
+impl SignatureStatus {
+    /// Lowercase name for status messages and the commit view.
+    pub fn describe(self) -> &'static str {
+        match self {
+            SignatureStatus::Good => "good",
+            SignatureStatus::Untrusted => "good, untrusted key",
+            SignatureStatus::Bad => "bad",
+            SignatureStatus::UnknownKey => "unknown key",
+            SignatureStatus::Unsigned => "unsigned",
+        }
+    }
+}
+
+/// Verification result with the details shown in the commit view.
+pub struct SignatureCheck {
+    /// Overall verdict.
+    pub status: SignatureStatus,
+    /// "gpg", "ssh" or "x509".
+    pub format: &'static str,
+    /// Key owner: the GPG user id or the SSH principal.
+    pub signer: Option<String>,
+    /// Human-readable output of the verifying tool.
+    pub output: String,
+}
+
+impl SignatureCheck {
+    fn unsigned() -> Self {
+        Self {
+            status: SignatureStatus::Unsigned,
+            format: "",
+            signer: None,
+            output: String::new(),
+        }
+    }
+}
+
+impl Repo {
+    /// Verify the signature of commit `id` with the local `gpg` or
+    /// `ssh-keygen`, honoring `gpg.program`, `gpg.ssh.program` and
+    /// `gpg.ssh.allowedSignersFile`.
+    pub fn verify_signature(&self, id: Oid) -> SignatureCheck {
+        let Ok((signature, data)) = self.inner.extract_signature(&id.git(), None) else {
+            return SignatureCheck::unsigned();
+        };
+        let config = self.inner.config().ok();
+        let get_string = |key: &str| config.as_ref().and_then(|c| c.get_string(key).ok());
+
+        let sig_file = sig_file(&signature);
+        let sig_path = match &sig_file {
+            Ok(f) => f.path(),
+            Err(e) => {
+                return SignatureCheck {
+                    status: SignatureStatus::UnknownKey,
+                    format: "",
+                    signer: None,
+                    output: format!("could not write signature: {e}"),
+                };
+            }
+        };
+
+        if signature.starts_with(b"-----BEGIN SSH SIGNATURE-----") {
+            let program = get_string("gpg.ssh.program").unwrap_or_else(|| "ssh-keygen".into());
+            let allowed = config
+                .as_ref()
+                .and_then(|c| c.get_path("gpg.ssh.allowedSignersFile").ok());
+            verify_ssh(&program, allowed.as_deref(), sig_path, &data)
+        } else if signature.starts_with(b"-----BEGIN PGP") {
+            let program = get_string("gpg.openpgp.program")
+                .or_else(|| get_string("gpg.program"))
+                .unwrap_or_else(|| "gpg".into());
+            verify_gpg(&program, sig_path, &data)
+        } else {
+            SignatureCheck {
+                status: SignatureStatus::UnknownKey,
+                format: "x509",
+                signer: None,
+                output: "X.509 signatures are not supported".to_string(),
+            }
+        }
+    }
+}
+
+/// Write the detached signature to a temporary file for the verifying
+/// tool, removed on drop. The file gets a random name and is created
+/// exclusively, readable by the user only, so other local users can neither
+/// plant nor swap it.
+fn sig_file(signature: &[u8]) -> std::io::Result<NamedTempFile> {
+    let mut file = tempfile::Builder::new()
+        .prefix("reki-")
+        .suffix(".sig")
+        .tempfile()?;
+    file.write_all(signature)?;
+    file.flush()?;
+    Ok(file)
+}
+
+/// Run `program` with `input` on stdin and collect its output.
+fn run(program: &str, args: &[&str], input: &[u8]) -> std::io::Result<Output> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    std::thread::scope(|s| {
+        // Feed stdin from another thread so a chatty tool can't deadlock us.
+        s.spawn(move || {
+            let _ = stdin.write_all(input);
+        });
+        child.wait_with_output()
+    })
+}
+
+fn verify_gpg(program: &str, sig_path: &Path, data: &[u8]) -> SignatureCheck {
+    let sig = sig_path.to_string_lossy();
+    let args = [
+        "--status-fd=1",
+        "--keyid-format=long",
+        "--verify",
+        &sig,
+        "-",
+    ];
+    match run(program, &args, data) {
+        Ok(out) => {
+            let (status, signer) = parse_gpg_status(&String::from_utf8_lossy(&out.stdout));
+            SignatureCheck {
+                status,
+                format: "gpg",
+                signer,
+                output: String::from_utf8_lossy(&out.stderr).into_owned(),
+            }
+        }
+        Err(e) => SignatureCheck {
+            status: SignatureStatus::UnknownKey,
+            format: "gpg",
+            signer: None,
+            output: format!("could not run {program}: {e}"),
+        },
+    }
+}
+
+/// Interpret `gpg --status-fd` output: the status and the signer's user id.
+/// A good signature only counts as [`SignatureStatus::Good`] when gpg also
+/// reports full or ultimate trust in the key.
+fn parse_gpg_status(status: &str) -> (SignatureStatus, Option<String>) {
+    let mut result = (SignatureStatus::UnknownKey, None);
+    let mut trusted = false;
+    for line in status.lines() {
+        let Some(rest) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        let (keyword, args) = rest.split_once(' ').unwrap_or((rest, ""));
+        // Arguments are "<keyid> <user id>".
+        let user_id = || args.split_once(' ').map(|(_, uid)| uid.to_string());
+        match keyword {
+            "GOODSIG" => result = (SignatureStatus::Untrusted, user_id()),
+            "TRUST_FULLY" | "TRUST_ULTIMATE" => trusted = true,
+            "BADSIG" | "EXPSIG" | "EXPKEYSIG" | "REVKEYSIG" => {
+                return (SignatureStatus::Bad, user_id());
+            }
+            "ERRSIG" | "NO_PUBKEY" => return (SignatureStatus::UnknownKey, None),
+            _ => {}
+        }
+    }
+    if trusted && result.0 == SignatureStatus::Untrusted {
+        result.0 = SignatureStatus::Good;
+    }
+    result
+}
+
+fn verify_ssh(
+    program: &str,
+    allowed_signers: Option<&Path>,
+    sig_path: &Path,
+    data: &[u8],
+) -> SignatureCheck {
+    let sig = sig_path.to_string_lossy();
+    let check = |status, signer, output: String| SignatureCheck {
+        status,
+        format: "ssh",
+        signer,
+        output,
+    };
+    let text = |out: &Output| {
+        format!(
+            "{}{}",
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        )
+    };
+
+    // Whether the signature matches the data at all, regardless of who made it.
+    let unverified = |reason: String| match run(
+        program,
+        &["-Y", "check-novalidate", "-n", "git", "-s", &sig],
+        data,
+    ) {
+        Ok(out) if out.status.success() => check(SignatureStatus::UnknownKey, None, reason),
+        Ok(out) => check(SignatureStatus::Bad, None, text(&out)),
+        Err(e) => check(
+            SignatureStatus::UnknownKey,
+            None,
+            format!("could not run {program}: {e}"),
+        ),
+    };
+
+    let Some(allowed) = allowed_signers else {
+        return unverified("gpg.ssh.allowedSignersFile is not configured".to_string());
+    };
+    let allowed = allowed.to_string_lossy();
+
+    let principal = run(
+        program,
+        &["-Y", "find-principals", "-f", &allowed, "-s", &sig],
+        &[],
+    )
+    .ok()
+    .filter(|out| out.status.success())
+    .and_then(|out| {
+        String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .next()
+            .map(str::to_string)
+    });
+    let Some(principal) = principal else {
+        return unverified(format!("no principal in {allowed} matches the key"));
+    };
+
+    let args = [
+        "-Y", "verify", "-f", &allowed, "-I", &principal, "-n", "git", "-s", &sig,
+    ];
+    match run(program, &args, data) {
+        Ok(out) if out.status.success() => {
+            check(SignatureStatus::Good, Some(principal), text(&out))
+        }
+        Ok(out) => check(SignatureStatus::Bad, Some(principal), text(&out)),
+        Err(e) => check(
+            SignatureStatus::UnknownKey,
+            None,
+            format!("could not run {program}: {e}"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gpg_status_lines() {
+        let good = "[GNUPG:] NEWSIG\n[GNUPG:] GOODSIG 0123456789ABCDEF Alice <a@x.org>\n[GNUPG:] VALIDSIG ...\n";
+        assert_eq!(
+            parse_gpg_status(good),
+            (
+                SignatureStatus::Untrusted,
+                Some("Alice <a@x.org>".to_string())
+            )
+        );
+        for trust in ["TRUST_FULLY 0 pgp", "TRUST_ULTIMATE 0 pgp"] {
+            let trusted = format!("{good}[GNUPG:] {trust}\n");
+            assert_eq!(parse_gpg_status(&trusted).0, SignatureStatus::Good);
+        }
+        let marginal = format!("{good}[GNUPG:] TRUST_MARGINAL 0 pgp\n");
+        assert_eq!(parse_gpg_status(&marginal).0, SignatureStatus::Untrusted);
+        let bad = "[GNUPG:] BADSIG 0123456789ABCDEF Alice <a@x.org>\n";
+        assert_eq!(parse_gpg_status(bad).0, SignatureStatus::Bad);
+        let unknown = "[GNUPG:] ERRSIG 0123456789ABCDEF 1 10 00 1700000000 9 -\n[GNUPG:] NO_PUBKEY 0123456789ABCDEF\n";
+        assert_eq!(
+            parse_gpg_status(unknown),
+            (SignatureStatus::UnknownKey, None)
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use super::commit::{CommitInfo, RefDecoration};
+use super::trailer::TrailerFilter;
+use super::{Oid, Repo, Result};
+
+/// Where the log gets its commits and ref decorations from.
+///
+/// [`Repo`] reads a git repository; [`MemoryRepo`](super::MemoryRepo) is an
+/// in-memory DAG for tests.
+pub trait CommitSource {
+    /// Load the next batch of commits, newest first. An empty batch means
+    /// the history is exhausted.
+    fn load_commits(&mut self) -> Result<Vec<CommitInfo>>;
+
+    /// Re-read references and restart loading from the first batch.
+    fn reload(&mut self) -> Result<()>;
+
+    /// Reference decorations pointing at `oid`.
+    fn refs_for(&self, oid: Oid) -> &[RefDecoration];
+
+    /// Revision range walked instead of HEAD, if any.
+    fn range(&self) -> Option<&str> {
+        None
+    }
+
+    /// Filter commits must match to be loaded, if any.
+    fn filter(&self) -> Option<&TrailerFilter> {
+        None
+    }
+
+    /// Pseudo-commits for uncommitted changes, shown above HEAD.
+    fn uncommitted_rows(&self) -> Vec<CommitInfo> {
+        Vec::new()
+    }
+}
+
+impl CommitSource for Repo {
+    fn load_commits(&mut self) -> Result<Vec<CommitInfo>> {
+        Repo::load_commits(self)
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        Repo::reload(self)
+    }
+
+    fn refs_for(&self, oid: Oid) -> &[RefDecoration] {
+        Repo::refs_for(self, oid)
+    }
+
+    fn range(&self) -> Option<&str> {
+        Repo::range(self)
+    }
+
+    fn filter(&self) -> Option<&TrailerFilter> {
+        Repo::filter(self)
+    }
+
+    fn uncommitted_rows(&self) -> Vec<CommitInfo> {
+        Repo::uncommitted_rows(self)
+    }
+}
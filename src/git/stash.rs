@@ -0,0 +1,69 @@
+use git2::StashApplyOptions;
+
+use super::diff::Diff;
+use super::{Oid, Repo, Result};
+
+/// One entry of the stash list.
+pub struct StashEntry {
+    /// Position in the stash list (`stash@{index}`).
+    pub index: usize,
+    /// Stash commit.
+    pub id: Oid,
+    /// Stash message (`WIP on main: ...` by default).
+    pub message: String,
+    /// Time the stash was created, as a Unix timestamp.
+    pub time: i64,
+}
+
+impl Repo {
+    /// List stash entries, newest first.
+    pub fn stashes(&mut self) -> Result<Vec<StashEntry>> {
+        let mut raw = Vec::new();
+        self.inner.stash_foreach(|index, message, id| {
+            raw.push((index, *id, message.to_string()));
+            true
+        })?;
+
+        Ok(raw
+            .into_iter()
+            .map(|(index, id, message)| {
+                let time = self.inner.find_commit(id).map_or(0, |c| c.time().seconds());
+                StashEntry {
+                    index,
+                    id: Oid::from_git(id),
+                    message,
+                    time,
+                }
+            })
+            .collect())
+    }
+
+    /// Diff a stash against the commit it was based on.
+    pub fn diff_stash(&self, id: Oid) -> Result<Diff> {
+        let base = self
+            .inner
+            .find_commit(id.git())
+            .and_then(|c| c.parent_id(0))?;
+        self.diff_commits(Oid::from_git(base), id)
+    }
+
+    /// Apply `stash@{index}` to the working tree, keeping the entry.
+    pub fn stash_apply(&mut self, index: usize) -> Result<()> {
+        let mut opts = StashApplyOptions::new();
+        self.inner.stash_apply(index, Some(&mut opts))?;
+        Ok(())
+    }
+
+    /// Apply `stash@{index}` and remove it from the list.
+    pub fn stash_pop(&mut self, index: usize) -> Result<()> {
+        let mut opts = StashApplyOptions::new();
+        self.inner.stash_pop(index, Some(&mut opts))?;
+        self.refresh_refs()
+    }
+
+    /// Remove `stash@{index}` without applying it.
+    pub fn stash_drop(&mut self, index: usize) -> Result<()> {
+        self.inner.stash_drop(index)?;
+        self.refresh_refs()
+    }
+}
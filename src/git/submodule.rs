@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+
+use super::diff::{Diff, DiffLine, LineKind, SubmoduleChange, SubmoduleCommit};
+use super::{Oid, Repo, Result};
+
+impl Repo {
+    /// Working directory of the submodule at `path` (relative to the
+    /// worktree), if it is checked out. Open it with [`Repo::open_with`]
+    /// and this repository's backend.
+    pub fn submodule_path(&self, path: &str) -> Option<PathBuf> {
+        let dir = self.inner.workdir()?.join(path);
+        // A submodule that was never initialized is an empty directory,
+        // which would open as this repository.
+        dir.join(".git").exists().then_some(dir)
+    }
+
+    /// Convert a libgit2 diff, listing the commits each submodule pointer
+    /// change moves over.
+    pub(super) fn load_diff(&self, diff: &mut git2::Diff<'_>) -> Result<Diff> {
+        let mut diff = Diff::from_git(diff)?;
+        for file in &mut diff.files {
+            let Some(change) = &mut file.submodule else {
+                continue;
+            };
+            if let (Some(old), Some(new)) = (change.old, change.new) {
+                change.commits = self.submodule_commits(&file.path, old, new);
+            }
+            let commits = change.commits.as_deref().unwrap_or_default();
+            file.additions = commits.iter().filter(|c| c.added).count();
+            file.deletions = commits.len() - file.additions;
+            file.lines = submodule_lines(&file.path, change);
+        }
+        Ok(diff)
+    }
+
+    /// Commits of the submodule at `path` that only `new` reaches, then
+    /// those only `old` reaches, or `None` if they cannot be read.
+    fn submodule_commits(&self, path: &str, old: Oid, new: Oid) -> Option<Vec<SubmoduleCommit>> {
+        let mut repo = Repo::open_with(&self.submodule_path(path)?, self.backend()).ok()?;
+        repo.set_order(self.order());
+        let mut commits = Vec::new();
+        for (from, to, added) in [(old, new, true), (new, old, false)] {
+            repo.set_range(Some(format!("{from}..{to}"))).ok()?;
+            for c in repo.walk().ok()? {
+                let c = c.ok()?;
+                commits.push(SubmoduleCommit {
+                    id: c.id,
+                    summary: c.summary,
+                    added,
+                });
+            }
+        }
+        Some(commits)
+    }
+}
+
+/// Lines shown for a submodule change, as `git diff --submodule=log`
+/// prints them: a header, then one line per commit.
+fn submodule_lines(path: &str, change: &SubmoduleChange) -> Vec<DiffLine> {
+    let short = |id: Option<Oid>| format!("{:.7}", id.unwrap_or_else(Oid::zero));
+    let (old, new) = (short(change.old), short(change.new));
+    let commits = change.commits.as_deref().unwrap_or_default();
+    let added = commits.iter().any(|c| c.added);
+    let removed = commits.iter().any(|c| !c.added);
+    let (dots, note) = match (change.old, change.new) {
+        (None, _) => ("...", " (new submodule)"),
+        (_, None) => ("...", " (submodule deleted)"),
+        _ if change.commits.is_none() => ("...", " (commits not present)"),
+        _ if removed && !added => ("...", " (rewind)"),
+        _ if removed => ("...", ""),
+        _ => ("..", ""),
+    };
+
+    let mut lines = vec![DiffLine {
+        kind: LineKind::Meta,
+        text: format!("Submodule {path} {old}{dots}{new}{note}"),
+    }];
+    lines.extend(commits.iter().map(|c| DiffLine {
+        kind: if c.added {
+            LineKind::Added
+        } else {
+            LineKind::Removed
+        },
+        text: format!("{:.7} {}", c.id, c.summary),
+    }));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::{Repository, Signature, Time};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::git::FileStatus;
+
+    fn commit(
+        repo: &Repository,
+        message: &str,
+        tree: git2::Oid,
+        parents: &[git2::Oid],
+    ) -> git2::Oid {
+        let sig = Signature::new("T", "t@x.org", &Time::new(1_700_000_000, 0)).unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let parents: Vec<_> = parents
+            .iter()
+            .map(|p| repo.find_commit(*p).unwrap())
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    /// A repository whose commits point the submodule `lib` at its first
+    /// commit, then at its third. Returns the two commits and those of
+    /// `lib`, which is checked out.
+    fn fixture() -> (TempDir, [Oid; 2], [Oid; 3]) {
+        let dir = TempDir::new().unwrap();
+        let lib = Repository::init(dir.path().join("lib")).unwrap();
+        let empty = lib.treebuilder(None).unwrap().write().unwrap();
+        let c1 = commit(&lib, "one", empty, &[]);
+        let c2 = commit(&lib, "two", empty, &[c1]);
+        let c3 = commit(&lib, "three", empty, &[c2]);
+
+        let repo = Repository::init(dir.path()).unwrap();
+        let tree = |lib: git2::Oid| {
+            let mut builder = repo.treebuilder(None).unwrap();
+            builder.insert("lib", lib, 0o160000).unwrap();
+            builder.write().unwrap()
+        };
+        let first = commit(&repo, "add lib", tree(c1), &[]);
+        let second = commit(&repo, "bump lib", tree(c3), &[first]);
+        (
+            dir,
+            [first, second].map(Oid::from_git),
+            [c1, c2, c3].map(Oid::from_git),
+        )
+    }
+
+    fn texts(diff: &Diff) -> Vec<&str> {
+        diff.files[0]
+            .lines
+            .iter()
+            .map(|l| l.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn lists_the_commits_a_pointer_moves_over() {
+        let (dir, [first, second], [c1, c2, c3]) = fixture();
+        let repo = Repo::open(dir.path()).unwrap();
+
+        let diff = repo.diff_commit(second).unwrap();
+        let file = &diff.files[0];
+        assert_eq!(
+            (file.path.as_str(), file.status),
+            ("lib", FileStatus::Modified)
+        );
+        let change = file.submodule.as_ref().unwrap();
+        assert_eq!((change.old, change.new), (Some(c1), Some(c3)));
+        let ids: Vec<Oid> = change.commits.iter().flatten().map(|c| c.id).collect();
+        assert_eq!(ids, [c3, c2]);
+        assert_eq!((file.additions, file.deletions), (2, 0));
+        assert_eq!(
+            texts(&diff),
+            [
+                format!("Submodule lib {c1:.7}..{c3:.7}"),
+                format!("{c3:.7} three"),
+                format!("{c2:.7} two"),
+            ]
+        );
+        assert_eq!(file.lines[1].kind, LineKind::Added);
+
+        let rewind = repo.diff_commits(second, first).unwrap();
+        assert_eq!(rewind.files[0].deletions, 2);
+        assert_eq!(
+            texts(&rewind)[0],
+            format!("Submodule lib {c3:.7}...{c1:.7} (rewind)")
+        );
+
+        let added = repo.diff_commit(first).unwrap();
+        assert_eq!(added.files[0].status, FileStatus::Added);
+        assert_eq!(
+            texts(&added),
+            [format!("Submodule lib 0000000...{c1:.7} (new submodule)")]
+        );
+    }
+
+    #[test]
+    fn opens_only_checked_out_submodules() {
+        let (dir, [_, second], [c1, _, c3]) = fixture();
+        let repo = Repo::open(dir.path()).unwrap();
+        let path = repo.submodule_path("lib").unwrap();
+        let lib = Repo::open(&path).unwrap();
+        assert_eq!(lib.resolve("HEAD").unwrap(), c3);
+        assert_eq!(repo.submodule_path("missing"), None);
+
+        std::fs::remove_dir_all(dir.path().join("lib")).unwrap();
+        std::fs::create_dir(dir.path().join("lib")).unwrap();
+        assert_eq!(repo.submodule_path("lib"), None);
+        let diff = repo.diff_commit(second).unwrap();
+        assert!(diff.files[0].submodule.as_ref().unwrap().commits.is_none());
+        assert_eq!(
+            texts(&diff),
+            [format!(
+                "Submodule lib {c1:.7}...{c3:.7} (commits not present)"
+            )]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+
+/// A `Key: value` line from the trailer block at the end of a commit message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trailer {
+    /// Trailer key as written (e.g. "Signed-off-by").
+    pub key: String,
+    /// Everything after the colon, trimmed.
+    pub value: String,
+}
+
+impl Trailer {
+    /// Whether this is a `Co-authored-by` trailer.
+    pub fn is_co_author(&self) -> bool {
+        self.key.eq_ignore_ascii_case("Co-authored-by")
+    }
+}
+
+/// Parse the trailer block of `message` with libgit2's trailer rules.
+pub(super) fn parse_trailers(message: &str) -> Vec<Trailer> {
+    let Ok(trailers) = git2::message_trailers_strs(message) else {
+        return Vec::new();
+    };
+    trailers
+        .iter()
+        .map(|(key, value)| Trailer {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+        .collect()
+}
+
+/// Replace `Name <email>` trailer values (co-authors, reviewers, ...) with
+/// their canonical identity from `.mailmap`, so they compare like authors.
+pub(super) fn canonicalize_trailers(trailers: &mut [Trailer], mailmap: &git2::Mailmap) {
+    for t in trailers {
+        let Some((name, rest)) = t.value.split_once('<') else {
+            continue;
+        };
+        let Some(email) = rest.strip_suffix('>') else {
+            continue;
+        };
+        let Ok(sig) = git2::Signature::new(name.trim(), email, &git2::Time::new(0, 0)) else {
+            continue;
+        };
+        if let Ok(canonical) = mailmap.resolve_signature(&sig) {
+            t.value = format!(
+                "{} <{}>",
+                canonical.name().unwrap_or(name.trim()),
+                canonical.email().unwrap_or(email)
+            );
+        }
+    }
+}
+
+/// Log filter keeping commits that carry a trailer with `key` and, if
+/// given, a value containing `value`. Both compare case-insensitively.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrailerFilter {
+    key: String,
+    value: Option<String>,
+}
+
+impl TrailerFilter {
+    /// Parse `Key` or `Key: value`. Returns `None` for an empty key.
+    pub fn parse(input: &str) -> Option<Self> {
+        let (key, value) = match input.split_once(':') {
+            Some((key, value)) => (key.trim(), Some(value.trim())),
+            None => (input.trim(), None),
+        };
+        if key.is_empty() {
+            return None;
+        }
+        Some(Self {
+            key: key.to_string(),
+            value: value.filter(|v| !v.is_empty()).map(str::to_lowercase),
+        })
+    }
+
+    /// Whether any of `trailers` passes the filter.
+    pub fn matches(&self, trailers: &[Trailer]) -> bool {
+        trailers.iter().any(|t| {
+            t.key.eq_ignore_ascii_case(&self.key)
+                && self
+                    .value
+                    .as_ref()
+                    .is_none_or(|v| t.value.to_lowercase().contains(v))
+        })
+    }
+}
+
+impl fmt::Display for TrailerFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}: {value}", self.key),
+            None => write!(f, "{}", self.key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_trailer_block() {
+        let message = "Fix crash\n\nDetails here.\n\nCo-authored-by: Bob <b@x.org>\nSigned-off-by: Alice <a@x.org>\n";
+        let trailers = parse_trailers(message);
+        assert_eq!(trailers.len(), 2);
+        assert!(trailers[0].is_co_author());
+        assert_eq!(trailers[0].value, "Bob <b@x.org>");
+        assert_eq!(trailers[1].key, "Signed-off-by");
+
+        assert!(parse_trailers("Subject only\n").is_empty());
+    }
+
+    #[test]
+    fn filters_by_key_and_value() {
+        let trailers = parse_trailers("x\n\nReviewed-by: Carol <c@x.org>\nFixes: #12\n");
+
+        let by_key = TrailerFilter::parse("reviewed-by").unwrap();
+        assert!(by_key.matches(&trailers));
+        let by_value = TrailerFilter::parse("Reviewed-by: carol").unwrap();
+        assert!(by_value.matches(&trailers));
+        let other = TrailerFilter::parse("Reviewed-by: dave").unwrap();
+        assert!(!other.matches(&trailers));
+        assert_eq!(by_value.to_string(), "Reviewed-by: carol");
+
+        assert!(TrailerFilter::parse(" : x").is_none());
+    }
+
+    #[test]
+    fn canonicalizes_identities() {
+        let mailmap = git2::Mailmap::from_buffer("Carol Smith <carol@x.org> <c@x.org>\n").unwrap();
+        let mut trailers = parse_trailers("x\n\nReviewed-by: carol <c@x.org>\nFixes: #12\n");
+        canonicalize_trailers(&mut trailers, &mailmap);
+        assert_eq!(trailers[0].value, "Carol Smith <carol@x.org>");
+        assert_eq!(trailers[1].value, "#12");
+    }
+}
@@ -0,0 +1,483 @@
+//! History traversal for the log, shared by both backends. Parents, commit
+//! times and generation numbers come from the commit graph where it covers
+//! a commit, and from the object database elsewhere.
+//!
+//! A commit is only yielded after all of its children. Generation numbers
+//! bound how far the walk has to look ahead to know that: every child of a
+//! commit has a higher generation, so once the commits down to it have been
+//! counted, its children are known, and the first rows of a large history
+//! are ready without walking all of it. Commits outside the commit graph
+//! have no generation and are counted up front, so without a commit graph
+//! all of history is read before the first commit comes out.
+//!
+//! A listed commit's node is freed for reuse, so the walk holds only the
+//! commits reached but not yet listed.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::commit_graph::CommitGraph;
+use super::{Oid, Repo, Result};
+
+/// Order the log lists commits in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Order {
+    /// Newest commit time first, but no commit before its children, as
+    /// `git log --date-order`. Commits with the same time come out in the
+    /// order they became ready.
+    #[default]
+    Time,
+    /// No commit before all of its children, keeping each line of history
+    /// together as far as possible (`git log --topo-order`).
+    Topo,
+}
+
+/// Generation of commits the commit graph does not cover: above all others.
+const INFINITY: u64 = u64::MAX;
+
+struct Node {
+    id: Oid,
+    parents: Vec<Oid>,
+    /// Commit time.
+    time: i64,
+    generation: u64,
+    /// Parents have been looked up.
+    expanded: bool,
+    /// Reachable from a hidden commit.
+    hidden: bool,
+    /// Number of children counted but not yet yielded, plus one; zero
+    /// before the first visit.
+    indegree: u32,
+}
+
+/// Where the walk reads parents and commit times from.
+pub(super) struct History<'a> {
+    pub(super) repo: &'a Repo,
+    pub(super) graph: Option<&'a CommitGraph>,
+}
+
+impl History<'_> {
+    fn node(&self, id: Oid) -> Result<Node> {
+        let from_graph = self.graph.and_then(|graph| {
+            let pos = graph.find(id)?;
+            let parents = graph
+                .parents(pos)?
+                .into_iter()
+                .map(|p| graph.id(p))
+                .collect::<Option<Vec<_>>>()?;
+            Some((parents, graph.commit_time(pos)?, graph.generation(pos)?))
+        });
+        let (parents, time, generation) = match from_graph {
+            Some(node) => node,
+            None => {
+                let (time, parents) = self.repo.read_commit(id)?;
+                (parents, time, INFINITY)
+            }
+        };
+        Ok(Node {
+            id,
+            parents,
+            time,
+            generation,
+            expanded: false,
+            hidden: false,
+            indegree: 0,
+        })
+    }
+}
+
+/// An incremental walk over the commits reachable from some commits and
+/// not from others. State is kept between calls to [`Walk::next`], so a
+/// log loaded in batches is walked once.
+pub(super) struct Walk {
+    order: Order,
+    nodes: Vec<Node>,
+    /// Slots of `nodes` freed for reuse.
+    free: Vec<usize>,
+    index: HashMap<Oid, usize>,
+    /// Commits whose children have all been yielded, next one on top.
+    queue: BinaryHeap<(i64, Reverse<u64>, usize)>,
+    /// Commits pushed on the queue so far, to break ties.
+    pushed: u64,
+    /// Unexpanded hidden commits by generation.
+    hidden_frontier: BinaryHeap<(u64, usize)>,
+    /// Commits whose children have not all been counted.
+    indegree_queue: BinaryHeap<(u64, usize)>,
+}
+
+impl Walk {
+    /// Start a walk from the `(commit, hidden)` pairs in `tips`.
+    pub(super) fn new(order: Order, tips: &[(Oid, bool)], history: &History) -> Result<Self> {
+        let mut walk = Self {
+            order,
+            nodes: Vec::new(),
+            free: Vec::new(),
+            index: HashMap::new(),
+            queue: BinaryHeap::new(),
+            pushed: 0,
+            hidden_frontier: BinaryHeap::new(),
+            indegree_queue: BinaryHeap::new(),
+        };
+
+        // Hidden tips win over the same commit pushed as visible.
+        let mut visible = Vec::new();
+        for &(id, hidden) in tips {
+            let node = walk.node(id, history)?;
+            if hidden {
+                walk.hide(node);
+            } else if !visible.contains(&node) {
+                visible.push(node);
+            }
+        }
+
+        visible.retain(|&node| !walk.nodes[node].hidden);
+        for &node in &visible {
+            if walk.nodes[node].indegree == 0 {
+                walk.nodes[node].indegree = 1;
+                walk.indegree_queue
+                    .push((walk.nodes[node].generation, node));
+            }
+        }
+        let depth = visible.iter().map(|&n| walk.nodes[n].generation).min();
+        walk.count_indegrees_to(depth.unwrap_or(INFINITY), history)?;
+        // The first tip comes out first among equals.
+        if order == Order::Topo {
+            visible.reverse();
+        }
+        for node in visible {
+            if walk.nodes[node].indegree == 1 {
+                walk.push(node);
+            }
+        }
+        Ok(walk)
+    }
+
+    /// The next commit of the log, or `None` at the end of history: the
+    /// top of the queue, whose children have all been yielded. Queue the
+    /// parents that this leaves without children to wait for.
+    pub(super) fn next(&mut self, history: &History) -> Result<Option<Oid>> {
+        while let Some((_, _, node)) = self.queue.pop() {
+            self.explore_hidden_to(self.nodes[node].generation, history)?;
+            if self.nodes[node].hidden {
+                continue;
+            }
+            for parent in self.nodes[node].parents.clone() {
+                let parent = self.node(parent, history)?;
+                let generation = self.nodes[parent].generation;
+                self.explore_hidden_to(generation, history)?;
+                if self.nodes[parent].hidden {
+                    continue;
+                }
+                self.count_indegrees_to(generation, history)?;
+                self.nodes[parent].indegree -= 1;
+                if self.nodes[parent].indegree == 1 {
+                    self.push(parent);
+                }
+            }
+            // Its children have all been counted, so nothing looks it up again.
+            let id = self.nodes[node].id;
+            self.index.remove(&id);
+            self.release(node);
+            return Ok(Some(id));
+        }
+        Ok(None)
+    }
+
+    /// The node of commit `id`, read on first use.
+    fn node(&mut self, id: Oid, history: &History) -> Result<usize> {
+        if let Some(&node) = self.index.get(&id) {
+            return Ok(node);
+        }
+        let commit = history.node(id)?;
+        let node = match self.free.pop() {
+            Some(node) => {
+                self.nodes[node] = commit;
+                node
+            }
+            None => {
+                self.nodes.push(commit);
+                self.nodes.len() - 1
+            }
+        };
+        self.index.insert(id, node);
+        Ok(node)
+    }
+
+    /// Free the slot of `node`, which the index no longer points to.
+    fn release(&mut self, node: usize) {
+        self.nodes[node].parents = Vec::new();
+        self.free.push(node);
+    }
+
+    /// Put `node` on the queue: newest first in time order, last in first
+    /// out in topo order, which keeps each line of history together.
+    fn push(&mut self, node: usize) {
+        self.pushed += 1;
+        let (time, seq) = match self.order {
+            Order::Time => (self.nodes[node].time, self.pushed),
+            Order::Topo => (0, u64::MAX - self.pushed),
+        };
+        self.queue.push((time, Reverse(seq), node));
+    }
+
+    /// Look up the parents of `node`, passing on its hidden mark.
+    fn expand(&mut self, node: usize, history: &History) -> Result<()> {
+        if self.nodes[node].expanded {
+            return Ok(());
+        }
+        self.nodes[node].expanded = true;
+        let hidden = self.nodes[node].hidden;
+        for parent in self.nodes[node].parents.clone() {
+            let parent = self.node(parent, history)?;
+            if hidden {
+                self.hide(parent);
+            }
+        }
+        Ok(())
+    }
+
+    /// Mark `node` and its expanded ancestors hidden.
+    fn hide(&mut self, node: usize) {
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            let commit = &mut self.nodes[node];
+            if commit.hidden {
+                continue;
+            }
+            commit.hidden = true;
+            if commit.expanded {
+                let parents = commit.parents.iter().filter_map(|p| self.index.get(p));
+                stack.extend(parents);
+            } else {
+                self.hidden_frontier.push((commit.generation, node));
+            }
+        }
+    }
+
+    /// Expand hidden commits down to `generation`, so that every commit of
+    /// that generation or above reachable from one is marked.
+    fn explore_hidden_to(&mut self, generation: u64, history: &History) -> Result<()> {
+        while let Some(&(top, node)) = self.hidden_frontier.peek()
+            && top >= generation
+        {
+            self.hidden_frontier.pop();
+            self.expand(node, history)?;
+        }
+        Ok(())
+    }
+
+    /// Count the children of commits down to `generation`, so that a commit
+    /// of that generation has all of its children counted.
+    fn count_indegrees_to(&mut self, generation: u64, history: &History) -> Result<()> {
+        while let Some(&(top, node)) = self.indegree_queue.peek()
+            && top >= generation
+        {
+            self.indegree_queue.pop();
+            self.explore_hidden_to(top, history)?;
+            if self.nodes[node].hidden {
+                continue;
+            }
+            for parent in self.nodes[node].parents.clone() {
+                let parent = self.node(parent, history)?;
+                let commit = &mut self.nodes[parent];
+                if commit.indegree == 0 {
+                    commit.indegree = 2;
+                    self.indegree_queue.push((commit.generation, parent));
+                } else {
+                    commit.indegree += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::{Oid, Repository};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::git::commit_graph::tests::{commits, git, write_graph};
+
+    /// Two branches merged twice, with a commit whose clock was behind its
+    /// parent's and one on a side branch that is newer than everything.
+    fn fixture() -> (TempDir, Vec<Oid>) {
+        let dir = TempDir::new().unwrap();
+        let ids = commits(
+            dir.path(),
+            &[
+                ("root", &[], 100),
+                ("a1", &[0], 200),
+                ("b1", &[0], 300),
+                ("a2", &[1], 400),
+                ("skewed", &[2], 50),
+                ("b3", &[4], 600),
+                ("merge", &[3, 5], 700),
+                ("late", &[2], 5_000),
+                ("a3", &[6], 800),
+                ("merge again", &[8, 7], 900),
+            ],
+        );
+        (dir, ids)
+    }
+
+    /// A merge whose first side is newer than its second, where the root
+    /// is newer than the second side but still has to wait for it.
+    fn skewed() -> (TempDir, Vec<Oid>) {
+        let dir = TempDir::new().unwrap();
+        let ids = commits(
+            dir.path(),
+            &[
+                ("root", &[], 400),
+                ("p", &[0], 450),
+                ("c", &[0], 460),
+                ("b2", &[2], 200),
+                ("b", &[3], 300),
+                ("a", &[1], 500),
+                ("merge", &[5, 4], 600),
+            ],
+        );
+        (dir, ids)
+    }
+
+    fn log(dir: &TempDir, order: Order, range: Option<&str>) -> Vec<Oid> {
+        let mut repo = Repo::open(dir.path()).unwrap();
+        repo.set_order(order);
+        repo.set_range(range.map(str::to_string)).unwrap();
+        repo.reload().unwrap();
+        repo.walk().unwrap().map(|c| c.unwrap().id.git()).collect()
+    }
+
+    #[test]
+    fn walks_the_same_with_and_without_a_graph() {
+        let (dir, ids) = fixture();
+        let late = format!("{}..main", ids[7]);
+        check_walks(&dir, &ids, &[None, Some(&late), Some("main~1")]);
+        let (dir, ids) = skewed();
+        check_walks(&dir, &ids, &[None, Some("main^2")]);
+    }
+
+    /// Check that no commit comes before its children, that time order is
+    /// the same as git's, and that the commit graph changes neither order.
+    fn check_walks(dir: &TempDir, ids: &[Oid], ranges: &[Option<&str>]) {
+        let repo = Repository::open(dir.path()).unwrap();
+        for &range in ranges {
+            let time_order = log(dir, Order::Time, range);
+            let topo_order = log(dir, Order::Topo, range);
+            for order in [&time_order, &topo_order] {
+                for (i, id) in order.iter().enumerate() {
+                    for parent in repo.find_commit(*id).unwrap().parent_ids() {
+                        let later = order[i..].contains(&parent);
+                        assert!(later || !order.contains(&parent), "{range:?}");
+                    }
+                }
+            }
+            let args = [
+                "log",
+                "--date-order",
+                "--format=%H",
+                range.unwrap_or("main"),
+            ];
+            if let Some(git_log) = git(dir.path(), &args) {
+                let git_log: Vec<Oid> = git_log.lines().map(|l| l.parse().unwrap()).collect();
+                assert_eq!(time_order, git_log, "{range:?}");
+            }
+
+            for (layers, corrected) in [
+                (vec![ids.to_vec()], true),
+                (vec![ids.to_vec()], false),
+                (vec![ids[..3].to_vec(), ids[3..].to_vec()], true),
+                // Commits outside the graph.
+                (vec![ids[..ids.len() - 3].to_vec()], true),
+            ] {
+                let info = repo.path().join("objects/info");
+                let _ = std::fs::remove_file(info.join("commit-graph"));
+                let _ = std::fs::remove_dir_all(info.join("commit-graphs"));
+                write_graph(&repo, &layers, corrected);
+                assert_eq!(log(dir, Order::Time, range), time_order);
+                assert_eq!(log(dir, Order::Topo, range), topo_order);
+            }
+            let _ = std::fs::remove_file(repo.path().join("objects/info/commit-graph"));
+        }
+    }
+
+    #[test]
+    fn keeps_branches_together_in_topo_order() {
+        let (dir, ids) = fixture();
+        let name = |id: &Oid| ids.iter().position(|i| i == id).unwrap();
+        let topo: Vec<usize> = log(&dir, Order::Topo, None).iter().map(name).collect();
+        assert_eq!(topo, [9, 7, 8, 6, 5, 4, 2, 3, 1, 0]);
+        let range: Vec<usize> = log(&dir, Order::Topo, Some(&format!("{}..main", ids[7])))
+            .iter()
+            .map(name)
+            .collect();
+        assert_eq!(range, [9, 8, 6, 5, 4, 3, 1]);
+    }
+
+    #[test]
+    fn ends_with_an_error_when_a_commit_is_missing() {
+        let (dir, ids) = fixture();
+        let repo = Repository::open(dir.path()).unwrap();
+        write_graph(&repo, std::slice::from_ref(&ids), true);
+        let hex = ids[1].to_string();
+        let object = repo.path().join("objects").join(&hex[..2]);
+        std::fs::remove_file(object.join(&hex[2..])).unwrap();
+        for order in [Order::Time, Order::Topo] {
+            let mut repo = Repo::open(dir.path()).unwrap();
+            repo.set_order(order);
+            // Parents come from the commit graph, so the commits before the
+            // missing one still come out, then the error.
+            let walked: Vec<_> = repo.walk().unwrap().collect();
+            assert!(walked.len() > 1, "{order:?}");
+            assert!(walked.last().unwrap().is_err(), "{order:?}");
+            assert!(walked[..walked.len() - 1].iter().all(Result::is_ok));
+        }
+        // Without it, history is read before the first commit comes out.
+        std::fs::remove_file(repo.path().join("objects/info/commit-graph")).unwrap();
+        let repo = Repo::open(dir.path()).unwrap();
+        assert!(repo.walk().is_err());
+    }
+
+    #[test]
+    fn reads_only_the_commits_the_first_rows_need() {
+        let dir = TempDir::new().unwrap();
+        let names: Vec<String> = (0..500).map(|i| format!("c{i}")).collect();
+        let parents: Vec<Vec<usize>> = (0..500)
+            .map(|i: usize| i.checked_sub(1).into_iter().collect())
+            .collect();
+        let list: Vec<(&str, &[usize], i64)> = (0..500)
+            .map(|i| (names[i].as_str(), parents[i].as_slice(), 1_000 + i as i64))
+            .collect();
+        let ids = commits(dir.path(), &list);
+        let tip = crate::git::Oid::from_git(ids[499]);
+        let repo = Repo::open(dir.path()).unwrap();
+        let objects = repo.inner.path().join("objects");
+
+        // Topological levels only.
+        write_graph(
+            &Repository::open(dir.path()).unwrap(),
+            std::slice::from_ref(&ids),
+            false,
+        );
+        let graph = CommitGraph::open(&objects);
+        let history = History {
+            repo: &repo,
+            graph: graph.as_ref(),
+        };
+        for order in [Order::Time, Order::Topo] {
+            let mut walk = Walk::new(order, &[(tip, false)], &history).unwrap();
+            for _ in 0..3 {
+                walk.next(&history).unwrap();
+            }
+            assert!(walk.nodes.len() < 10, "{order:?}: {}", walk.nodes.len());
+            // Listed commits make room for the next ones.
+            let mut listed = 3;
+            while walk.next(&history).unwrap().is_some() {
+                listed += 1;
+            }
+            assert_eq!(listed, 500);
+            assert!(walk.nodes.len() < 10, "{order:?}: {}", walk.nodes.len());
+        }
+    }
+}
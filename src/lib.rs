@@ -0,0 +1,30 @@
+//! Git log model behind the reki viewer.
+//!
+//! [`Repo`] loads commits lazily in batches and looks up the refs that
+//! point at them ([`RefDecoration`]), and [`Graph`] lays them out as ASCII
+//! branch lines one row at a time:
+//!
+//! ```no_run
+//! use reki::{Graph, Repo};
+//!
+//! let mut repo = Repo::open(std::path::Path::new("."))?;
+//! let mut graph = Graph::new();
+//! for c in repo.load_commits()? {
+//!     println!("{}{:.7} {}", graph.next_row(c.id, &c.parent_ids), c.id, c.summary);
+//! }
+//! # Ok::<(), reki::Error>(())
+//! ```
+//!
+//! Errors are [`Error`] values; nothing in the library panics on a bad
+//! repository or revision.
+
+#![warn(missing_docs)]
+
+/// Repository access: the commit source, graph layout and ref model, plus
+/// the diff, rebase, stash and reflog operations used by the viewer.
+pub mod git;
+
+pub use git::{
+    Backend, CommitInfo, CommitSource, Error, Graph, Identity, Oid, RefDecoration, RefKind, Repo,
+    Result, TrailerFilter,
+};
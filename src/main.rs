@@ -1,27 +1,71 @@
 mod app;
+mod cli;
+mod clipboard;
 mod event;
-mod git;
+mod export;
+mod format;
+mod print;
+mod prompt;
 mod ui;
+mod watcher;
+
+use std::io::{self, IsTerminal};
 
 use anyhow::Result;
 
 use app::App;
+use cli::{Args, YankTarget};
 use event::EventHandler;
 use ui::Tui;
 
 fn main() -> Result<()> {
-    let mut app = App::new()?;
+    format::init_local_offset();
+    let args = Args::parse()?;
+    let repo = args.open_repo()?;
+    if let Some(format) = args.export {
+        let mut out = io::BufWriter::new(io::stdout().lock());
+        let result = export::export(&repo, format, args.full_message, args.max_count, &mut out);
+        return ignore_broken_pipe(result.map(drop));
+    }
+    if args.print || !io::stdout().is_terminal() {
+        return ignore_broken_pipe(print::run(repo, &args));
+    }
+    let mut app = App::new(repo)?;
     let mut tui = Tui::new()?;
     tui.enter()?;
 
     while !app.should_quit {
+        if let Some(text) = app.clipboard.take() {
+            tui.copy(&text)?;
+        }
         tui.draw(&mut app)?;
 
         if let Some(ev) = EventHandler::poll()? {
             app.handle_event(ev);
         }
+        app.tick();
     }
 
     tui.exit()?;
+
+    if let (Some(target), Some(text)) = (&args.yank_to, &app.last_yank) {
+        match target {
+            YankTarget::Stdout => println!("{text}"),
+            YankTarget::File(path) => std::fs::write(path, format!("{text}\n"))?,
+        }
+    }
     Ok(())
 }
+
+/// Treat a closed stdout (`reki | head`) as success.
+fn ignore_broken_pipe(result: Result<()>) -> Result<()> {
+    match result {
+        Err(e)
+            if e.downcast_ref::<io::Error>()
+                .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) =>
+        {
+            Ok(())
+        }
+        other => other,
+    }
+}
@@ -0,0 +1,96 @@
+use std::io::{self, IsTerminal, Write};
+
+use anyhow::Result;
+use reki::git::{CommitInfo, Graph, RefDecoration, RefKind, Repo};
+use unicode_truncate::UnicodeTruncateStr;
+
+use crate::cli::{Args, ColorMode};
+use crate::format::format_time;
+
+/// Same author column width as the log view.
+const AUTHOR_MAX_WIDTH: usize = 16;
+
+/// Write the log to stdout as text instead of starting the TUI, for pipes
+/// and scripts.
+pub fn run(mut repo: Repo, args: &Args) -> Result<()> {
+    let color = match args.color {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+    };
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    write_log(&mut repo, args.max_count, color, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Stream commits batch by batch, with the graph, until `max_count`.
+fn write_log(
+    repo: &mut Repo,
+    max_count: Option<usize>,
+    color: bool,
+    out: &mut impl Write,
+) -> Result<()> {
+    let mut graph = Graph::new();
+    // A filtered log is a flat list, as in the viewer.
+    let filtered = repo.filter().is_some();
+    let mut remaining = max_count.unwrap_or(usize::MAX);
+    while remaining > 0 {
+        let batch = repo.load_commits()?;
+        if batch.is_empty() {
+            break;
+        }
+        for c in batch.iter().take(remaining) {
+            let parents = if filtered { &[][..] } else { &c.parent_ids };
+            let graph_str = graph.next_row(c.id, parents);
+            let refs = repo.refs_for(c.id);
+            writeln!(out, "{}", format_row(c, refs, &graph_str, color))?;
+        }
+        remaining = remaining.saturating_sub(batch.len());
+    }
+    Ok(())
+}
+
+/// One log line: graph, hash, author date, author, refs and summary.
+fn format_row(c: &CommitInfo, refs: &[RefDecoration], graph_str: &str, color: bool) -> String {
+    let paint = |text: &str, code: &str| {
+        if color {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    };
+
+    let mut line = String::new();
+    for ch in graph_str.chars() {
+        line += &match ch {
+            '*' => paint("*", "1"),
+            '|' => paint("|", "90"),
+            _ => ch.to_string(),
+        };
+    }
+    line += &paint(&format!("{:.7}", c.id), "33");
+    line.push(' ');
+    line += &paint(&format_time(c.author.time), "32");
+    line.push(' ');
+
+    let (author, width) = c.author.name.unicode_truncate(AUTHOR_MAX_WIDTH);
+    let padded = format!("{author}{:w$}", "", w = AUTHOR_MAX_WIDTH - width);
+    line += &paint(&padded, "34");
+    line.push(' ');
+
+    for r in refs {
+        let (code, label) = match r.kind {
+            RefKind::Head => ("1;36", "HEAD".to_string()),
+            RefKind::Branch => ("1;32", r.name.clone()),
+            RefKind::Remote => ("1;31", r.name.clone()),
+            RefKind::Tag => ("33", format!("tag: {}", r.name)),
+            RefKind::AnnotatedTag => ("1;33", format!("tag: {}", r.name)),
+            RefKind::Stash => ("1;35", r.name.clone()),
+        };
+        line += &paint(&format!("({label})"), code);
+        line.push(' ');
+    }
+    line += &c.summary;
+    line
+}
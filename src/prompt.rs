@@ -0,0 +1,211 @@
+use reki::git::Oid;
+
+use crate::app::Action;
+
+/// What the text entered in a prompt will be used for.
+pub enum PromptKind {
+    /// Jump to the commit named by a revspec (`:`).
+    Goto,
+    /// Name of a new branch at `target`.
+    CreateBranch { target: Oid },
+    /// Name of a new tag at `target`.
+    CreateTag { target: Oid, annotated: bool },
+    /// Message of the annotated tag `name`.
+    TagMessage { target: Oid, name: String },
+    /// Local branch to rename.
+    RenameBranch,
+    /// New name for the local branch `from`.
+    RenameBranchTo { from: String },
+    /// Local branch to delete.
+    DeleteBranch,
+    /// Local branch to check out.
+    CheckoutBranch,
+    /// Parent number for cherry-picking or reverting the merge `target`.
+    Mainline {
+        target: Oid,
+        revert: bool,
+        parents: usize,
+    },
+    /// Reference whose reflog to show.
+    Reflog,
+    /// Trailer `Key` or `Key: value` to filter the log by (empty clears).
+    TrailerFilter,
+    /// File to export the log to; the extension picks the format.
+    Export,
+    /// New summary for the rebase todo entry at `index`.
+    Reword { index: usize },
+    /// Yes/no confirmation before running `action`.
+    Confirm(Action),
+}
+
+impl PromptKind {
+    /// Leading label drawn before the input text.
+    pub fn label(&self) -> String {
+        match self {
+            PromptKind::Goto => ":".to_string(),
+            PromptKind::CreateBranch { target } => format!("New branch at {target:.7}: "),
+            PromptKind::CreateTag {
+                target,
+                annotated: false,
+            } => format!("New tag at {target:.7}: "),
+            PromptKind::CreateTag {
+                target,
+                annotated: true,
+            } => format!("New annotated tag at {target:.7}: "),
+            PromptKind::TagMessage { name, .. } => format!("Message for tag '{name}': "),
+            PromptKind::RenameBranch => "Rename branch: ".to_string(),
+            PromptKind::RenameBranchTo { from } => format!("Rename '{from}' to: "),
+            PromptKind::DeleteBranch => "Delete branch: ".to_string(),
+            PromptKind::CheckoutBranch => "Check out branch: ".to_string(),
+            PromptKind::Mainline { parents, .. } => {
+                format!("Merge commit: mainline parent (1-{parents}): ")
+            }
+            PromptKind::Reflog => "Reflog for: ".to_string(),
+            PromptKind::TrailerFilter => "Filter by trailer: ".to_string(),
+            PromptKind::Export => "Export to (.json, .ndjson, .csv): ".to_string(),
+            PromptKind::Reword { .. } => "Reword: ".to_string(),
+            PromptKind::Confirm(action) => format!("{}? [y/N] ", action.describe()),
+        }
+    }
+}
+
+/// Single-line text input shown in the status bar.
+pub struct Prompt {
+    pub kind: PromptKind,
+    /// Text entered so far (the cursor is always at the end).
+    pub input: String,
+    /// Tab-completion state, reset by any edit.
+    completion: Option<Completion>,
+}
+
+/// Candidates for the fragment being completed, cycled on repeated Tab.
+struct Completion {
+    /// Input up to the start of the completed fragment.
+    prefix: String,
+    candidates: Vec<String>,
+    index: usize,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind) -> Self {
+        Self::with_input(kind, String::new())
+    }
+
+    /// Create a prompt with pre-filled input.
+    pub fn with_input(kind: PromptKind, input: String) -> Self {
+        Self {
+            kind,
+            input,
+            completion: None,
+        }
+    }
+
+    pub fn push(&mut self, ch: char) {
+        self.completion = None;
+        self.input.push(ch);
+    }
+
+    pub fn backspace(&mut self) {
+        self.completion = None;
+        self.input.pop();
+    }
+
+    pub fn clear(&mut self) {
+        self.completion = None;
+        self.input.clear();
+    }
+
+    /// Complete the trailing ref-name fragment of the input against `names`.
+    ///
+    /// The first call replaces the fragment with the first matching name;
+    /// further calls cycle through the remaining matches.
+    pub fn complete(&mut self, names: &[String]) {
+        if let Some(c) = &mut self.completion {
+            c.index = (c.index + 1) % c.candidates.len();
+            self.input = format!("{}{}", c.prefix, c.candidates[c.index]);
+            return;
+        }
+
+        let start = fragment_start(&self.input);
+        let (prefix, fragment) = self.input.split_at(start);
+        let candidates: Vec<String> = names
+            .iter()
+            .filter(|n| n.starts_with(fragment))
+            .cloned()
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+
+        let prefix = prefix.to_string();
+        self.input = format!("{prefix}{}", candidates[0]);
+        self.completion = Some(Completion {
+            prefix,
+            candidates,
+            index: 0,
+        });
+    }
+}
+
+/// Byte offset where the trailing ref-name fragment of a revspec begins
+/// (e.g. after `^`, `~`, `..` or `@{`).
+fn fragment_start(input: &str) -> usize {
+    let is_ref_char = |c: char| c.is_alphanumeric() || matches!(c, '/' | '-' | '_' | '.');
+    let mut start = input
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_ref_char(c))
+        .last()
+        .map_or(input.len(), |(i, _)| i);
+    // In a range like `main..feat` only the right-hand side is completed.
+    if let Some(pos) = input[start..].rfind("..") {
+        start += pos + 2;
+    }
+    start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names() -> Vec<String> {
+        ["HEAD", "main", "origin/main", "origin/master", "v1.0"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn completes_and_cycles() {
+        let mut p = Prompt::new(PromptKind::Goto);
+        for ch in "origin/ma".chars() {
+            p.push(ch);
+        }
+        p.complete(&names());
+        assert_eq!(p.input, "origin/main");
+        p.complete(&names());
+        assert_eq!(p.input, "origin/master");
+        p.complete(&names());
+        assert_eq!(p.input, "origin/main");
+    }
+
+    #[test]
+    fn completes_after_revspec_operators() {
+        let mut p = Prompt::new(PromptKind::Goto);
+        for ch in "HEAD..v".chars() {
+            p.push(ch);
+        }
+        p.complete(&names());
+        assert_eq!(p.input, "HEAD..v1.0");
+    }
+
+    #[test]
+    fn no_match_leaves_input_unchanged() {
+        let mut p = Prompt::new(PromptKind::Goto);
+        for ch in "main~2".chars() {
+            p.push(ch);
+        }
+        p.complete(&names());
+        assert_eq!(p.input, "main~2");
+    }
+}
@@ -0,0 +1,125 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+use reki::git::{FileDiff, FileStatus, LineKind, Oid};
+
+use crate::app::{DiffFocus, DiffView};
+use crate::format::{DetailKind, DetailLine};
+
+/// Render a file list next to the selected file's patch.
+pub fn render(frame: &mut Frame, view: &DiffView, area: Rect) {
+    let [files_area, patch_area] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(area);
+
+    let border = |focused: bool| {
+        Style::default().fg(if focused {
+            Color::Gray
+        } else {
+            Color::DarkGray
+        })
+    };
+
+    // File list.
+    let items: Vec<ListItem> = view.diff.files.iter().map(file_item).collect();
+    let title = format!(
+        " {} ({} files) ",
+        view.source.title(),
+        view.diff.files.len()
+    );
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(border(view.focus == DiffFocus::Files)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("\u{25b8} ");
+    let mut state = ListState::default();
+    state.select(Some(view.selected_file));
+    frame.render_stateful_widget(list, files_area, &mut state);
+
+    // Commit header followed by the patch of the selected file.
+    let file = view.diff.files.get(view.selected_file);
+    let mut lines: Vec<Line> = view.header.iter().map(detail_line).collect();
+    if let Some(f) = file {
+        lines.extend(f.lines.iter().map(|l| patch_line(l.kind, &l.text)));
+    }
+    let title = file.map_or_else(String::new, |f| format!(" {} ", f.path));
+    let patch = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(border(view.focus == DiffFocus::Patch)),
+        )
+        .scroll((u16::try_from(view.scroll).unwrap_or(u16::MAX), 0));
+    frame.render_widget(patch, patch_area);
+}
+
+/// One entry of the file list: status, path and line counts.
+fn file_item(f: &FileDiff) -> ListItem<'static> {
+    let status_color = match f.status {
+        FileStatus::Added => Color::Green,
+        FileStatus::Deleted => Color::Red,
+        FileStatus::Renamed | FileStatus::Copied => Color::Cyan,
+        FileStatus::Modified | FileStatus::TypeChange => Color::Yellow,
+    };
+    let mut path = match &f.old_path {
+        Some(old) => format!("{old} \u{2192} {}", f.path),
+        None => f.path.clone(),
+    };
+    if let Some(change) = &f.submodule {
+        let old = change.old.unwrap_or_else(Oid::zero);
+        let new = change.new.unwrap_or_else(Oid::zero);
+        path += &format!(" ({old:.7}..{new:.7})");
+    }
+    ListItem::new(Line::from(vec![
+        Span::styled(
+            format!("{} ", f.status.code()),
+            Style::default().fg(status_color),
+        ),
+        Span::raw(path),
+        Span::styled(
+            format!(" +{}", f.additions),
+            Style::default().fg(Color::Green),
+        ),
+        Span::styled(
+            format!(" -{}", f.deletions),
+            Style::default().fg(Color::Red),
+        ),
+    ]))
+}
+
+/// Style one line of the commit header by kind.
+fn detail_line(l: &DetailLine) -> Line<'static> {
+    let style = match l.kind {
+        DetailKind::Title => Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+        DetailKind::Field => Style::default().fg(Color::Blue),
+        DetailKind::Message => Style::default(),
+        DetailKind::Signature => Style::default().fg(Color::DarkGray),
+    };
+    Line::from(Span::styled(l.text.clone(), style))
+}
+
+/// Colorize one patch line by kind.
+fn patch_line(kind: LineKind, text: &str) -> Line<'static> {
+    let (prefix, style) = match kind {
+        LineKind::Hunk => ("", Style::default().fg(Color::Cyan)),
+        LineKind::Context => (" ", Style::default()),
+        LineKind::Added => ("+", Style::default().fg(Color::Green)),
+        LineKind::Removed => ("-", Style::default().fg(Color::Red)),
+        LineKind::Meta => ("", Style::default().add_modifier(Modifier::ITALIC)),
+    };
+    Line::from(Span::styled(format!("{prefix}{text}"), style))
+}
@@ -5,11 +5,12 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
 };
+use reki::git::{RefDecoration, RefKind, SignatureStatus};
 use unicode_truncate::UnicodeTruncateStr;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::app::App;
-use crate::git::{CommitInfo, RefKind};
+use crate::app::{App, Row};
+use crate::format::format_time;
 
 /// Fixed column widths for alignment.
 const DATE_WIDTH: usize = 16; // "YYYY-MM-DD HH:MM"
@@ -17,15 +18,18 @@ const AUTHOR_MAX_WIDTH: usize = 16;
 
 /// Render the log view into the given area.
 pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
-    let graph_max_width = app.graph_lines.iter().map(String::len).max().unwrap_or(0);
+    let graph_max_width = app.commits.graph_width();
 
     let rows: Vec<Vec<Span<'static>>> = app
         .commits
         .iter()
-        .enumerate()
-        .map(|(idx, c)| {
-            let graph_str = app.graph_lines.get(idx).map_or("", String::as_str);
-            build_commit_line(c, graph_str, graph_max_width)
+        .map(|c| {
+            let marked = app.marked == Some(c.id());
+            let signature = app.signatures.get(&c.id()).copied();
+            let refs = app.refs_for(c.id());
+            let name = c.name(app.show_committer, app.raw_identities);
+            let time = c.time(app.show_committer);
+            build_commit_line(c, refs, name, time, marked, signature, graph_max_width)
         })
         .collect();
 
@@ -47,7 +51,16 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
         })
         .collect();
 
-    let title = format!(" Log ({}) ", app.commits.len());
+    let mut title = format!(" Log ({}) ", app.commits.len());
+    if let Some(path) = &app.submodule {
+        title += &format!("submodule {path} ");
+    }
+    if let Some(range) = app.range() {
+        title += &format!("{range} ");
+    }
+    if let Some(f) = app.trailer_filter() {
+        title += &format!("[{f}] ");
+    }
 
     let list = List::new(items)
         .block(
@@ -68,15 +81,33 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_stateful_widget(list, area, &mut state);
 }
 
-/// Build styled spans for a single commit row.
+/// Build styled spans for a single commit row, with the name and date of
+/// the author or the committer.
 fn build_commit_line(
-    c: &CommitInfo,
-    graph_str: &str,
+    c: Row<'_>,
+    refs: &[RefDecoration],
+    author: &str,
+    time: i64,
+    marked: bool,
+    signature: Option<SignatureStatus>,
     graph_max_width: usize,
 ) -> Vec<Span<'static>> {
     let mut spans = Vec::with_capacity(12);
 
+    // Mark column.
+    spans.push(if marked {
+        Span::styled(
+            "\u{25cf} ",
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        )
+    } else {
+        Span::raw("  ")
+    });
+
     // Graph.
+    let graph_str = c.graph().to_string();
     for ch in graph_str.chars() {
         let style = match ch {
             '*' => Style::default()
@@ -92,31 +123,55 @@ fn build_commit_line(
         spans.push(Span::raw(" ".repeat(pad)));
     }
 
-    // Hash.
-    spans.push(Span::styled(
-        format!("{:.7}", c.id),
-        Style::default().fg(Color::Yellow),
-    ));
+    // Hash (blank for uncommitted changes).
+    let hash = if c.uncommitted().is_some() {
+        " ".repeat(7)
+    } else {
+        format!("{:.7}", c.id())
+    };
+    spans.push(Span::styled(hash, Style::default().fg(Color::Yellow)));
     spans.push(Span::raw(" "));
 
-    // Date.
+    // Signature status (blank while unsigned or not yet verified).
+    spans.push(match signature {
+        Some(SignatureStatus::Good) => Span::styled("\u{2713} ", Style::default().fg(Color::Green)),
+        Some(SignatureStatus::Untrusted) => {
+            Span::styled("\u{2713} ", Style::default().fg(Color::Yellow))
+        }
+        Some(SignatureStatus::Bad) => Span::styled(
+            "\u{2717} ",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Some(SignatureStatus::UnknownKey) => Span::styled("? ", Style::default().fg(Color::Yellow)),
+        Some(SignatureStatus::Unsigned) | None => Span::raw("  "),
+    });
+
+    // Date, then a marker when author and committer differ.
     spans.push(Span::styled(
-        format!("{:<w$}", c.formatted_time(), w = DATE_WIDTH),
+        format!("{:<w$}", format_time(time), w = DATE_WIDTH),
         Style::default().fg(Color::Green),
     ));
     spans.push(Span::raw(" "));
+    spans.push(if c.rewritten() {
+        Span::styled("~", Style::default().fg(Color::Magenta))
+    } else {
+        Span::raw(" ")
+    });
 
-    // Author (truncated, padded).
-    let (truncated, truncated_width) = c.author.unicode_truncate(AUTHOR_MAX_WIDTH);
-    let padding = AUTHOR_MAX_WIDTH - truncated_width;
-    let author_display = if truncated.len() < c.author.len() {
-        format!(
-            "{truncated}\u{2026}{:>w$}",
-            "",
-            w = padding.saturating_sub(1)
-        )
+    // Author (truncated, padded), with the number of co-authors.
+    let co_authors = match c.co_author_count() {
+        0 => String::new(),
+        n => format!(" +{n}"),
+    };
+    let name_width = AUTHOR_MAX_WIDTH - co_authors.len();
+    let author_display = if author.width() > name_width {
+        // Leave room for the ellipsis so the column keeps its width.
+        let (truncated, truncated_width) = author.unicode_truncate(name_width - 1);
+        let padding = name_width - 1 - truncated_width;
+        format!("{truncated}\u{2026}{co_authors}{:>padding$}", "")
     } else {
-        format!("{truncated}{:>w$}", "", w = padding)
+        let padding = name_width - author.width();
+        format!("{author}{co_authors}{:>padding$}", "")
     };
     spans.push(Span::styled(
         author_display,
@@ -125,24 +180,34 @@ fn build_commit_line(
     spans.push(Span::raw(" "));
 
     // Ref decorations.
-    for r in &c.refs {
+    for r in refs {
         let (color, label) = match r.kind {
             RefKind::Head => (Color::Cyan, "HEAD".to_string()),
             RefKind::Branch => (Color::Green, r.name.clone()),
             RefKind::Remote => (Color::Red, r.name.clone()),
-            RefKind::Tag => (Color::Yellow, format!("\u{1f3f7} {}", r.name)),
+            RefKind::Tag | RefKind::AnnotatedTag => {
+                (Color::Yellow, format!("\u{1f3f7} {}", r.name))
+            }
+            RefKind::Stash => (Color::Magenta, r.name.clone()),
         };
-        spans.push(Span::styled(
-            format!("({label}) "),
-            Style::default().fg(color).add_modifier(Modifier::BOLD),
-        ));
+        // Lightweight tags are drawn without bold to set them apart from
+        // annotated ones.
+        let style = match r.kind {
+            RefKind::Tag => Style::default().fg(color),
+            _ => Style::default().fg(color).add_modifier(Modifier::BOLD),
+        };
+        spans.push(Span::styled(format!("({label}) "), style));
     }
 
     // Summary.
-    spans.push(Span::styled(
-        c.summary.clone(),
-        Style::default().fg(Color::Reset),
-    ));
+    let summary_style = if c.uncommitted().is_some() {
+        Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::ITALIC)
+    } else {
+        Style::default().fg(Color::Reset)
+    };
+    spans.push(Span::styled(c.summary().to_string(), summary_style));
 
     spans
 }
@@ -161,6 +226,9 @@ fn scroll_spans(spans: Vec<Span<'static>>, offset: usize) -> Vec<Span<'static>>
             let w = ch.width().unwrap_or(0);
             if col >= offset {
                 buf.push(ch);
+            } else if col + w > offset {
+                // Keep columns aligned when a wide character is cut in half.
+                buf.extend(std::iter::repeat_n(' ', col + w - offset));
             }
             col += w;
         }
@@ -1,15 +1,27 @@
+mod diff_view;
 mod log_view;
+mod rebase_view;
+mod reflog_view;
+mod stash_view;
+mod status_bar;
+#[cfg(test)]
+mod tests;
 
-use std::io::{self, Stdout};
+use std::io::{self, Stdout, Write};
 
 use anyhow::Result;
 use crossterm::{
     execute,
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{Frame, Terminal, prelude::CrosstermBackend};
+use ratatui::{
+    Frame, Terminal,
+    layout::{Constraint, Layout},
+    prelude::CrosstermBackend,
+};
 
-use crate::app::App;
+use crate::app::{App, View};
+use crate::clipboard;
 
 type Term = Terminal<CrosstermBackend<Stdout>>;
 
@@ -41,6 +53,14 @@ impl Tui {
         Ok(())
     }
 
+    /// Copy `text` to the terminal's clipboard with an OSC 52 sequence.
+    pub fn copy(&mut self, text: &str) -> Result<()> {
+        let backend = self.terminal.backend_mut();
+        backend.write_all(clipboard::osc52(text).as_bytes())?;
+        backend.flush()?;
+        Ok(())
+    }
+
     /// Draw the UI.
     pub fn draw(&mut self, app: &mut App) -> Result<()> {
         self.terminal.draw(|frame| {
@@ -52,8 +72,16 @@ impl Tui {
 
 /// Render the entire UI.
 fn render(frame: &mut Frame, app: &mut App) {
-    let area = frame.area();
+    let [main, status] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
     // Update page_height: area height minus 2 for the border.
-    app.page_height = (area.height as usize).saturating_sub(2);
-    log_view::render(frame, app, area);
+    app.page_height = (main.height as usize).saturating_sub(2);
+    match app.views.last() {
+        Some(View::Diff(view)) => diff_view::render(frame, view, main),
+        Some(View::Rebase(view)) => rebase_view::render(frame, view, main),
+        Some(View::Stash(view)) => stash_view::render(frame, view, main),
+        Some(View::Reflog(view)) => reflog_view::render(frame, view, main),
+        None => log_view::render(frame, app, main),
+    }
+    status_bar::render(frame, app, status);
 }
@@ -0,0 +1,81 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+use reki::git::TodoAction;
+
+use crate::app::RebaseView;
+
+/// Render the rebase todo list, oldest commit first.
+pub fn render(frame: &mut Frame, view: &RebaseView, area: Rect) {
+    let next = view.run.as_ref().map(|run| run.next);
+    let items: Vec<ListItem> = view
+        .todo
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            // Progress marker while a rebase is paused.
+            let progress = match next {
+                Some(n) if idx < n => Span::styled("\u{2713} ", Style::default().fg(Color::Green)),
+                Some(n) if idx == n => Span::styled("! ", Style::default().fg(Color::Red)),
+                _ => Span::raw("  "),
+            };
+            let action_color = match entry.action {
+                TodoAction::Pick => Color::Green,
+                TodoAction::Reword => Color::Cyan,
+                TodoAction::Squash | TodoAction::Fixup => Color::Magenta,
+                TodoAction::Drop => Color::Red,
+            };
+            let summary_style = if entry.action == TodoAction::Drop {
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::CROSSED_OUT)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Line::from(vec![
+                progress,
+                Span::styled(
+                    format!("{:<7}", entry.action.name()),
+                    Style::default()
+                        .fg(action_color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" "),
+                Span::styled(
+                    format!("{:.7}", entry.id),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(" "),
+                Span::styled(entry.summary.clone(), summary_style),
+            ]))
+        })
+        .collect();
+
+    let paused = if view.run.is_some() { " (paused)" } else { "" };
+    let title = format!(
+        " Rebase {} commits onto {:.7}{paused} ",
+        view.todo.len(),
+        view.onto
+    );
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("\u{25b8} ");
+
+    let mut state = ListState::default();
+    state.select(Some(view.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
@@ -0,0 +1,60 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+use crate::app::ReflogView;
+use crate::format::format_time;
+
+/// Render a reflog: selector, old and new hash, time and action message.
+pub fn render(frame: &mut Frame, view: &ReflogView, area: Rect) {
+    let short = view.name.strip_prefix("refs/heads/").unwrap_or(&view.name);
+    let items: Vec<ListItem> = view
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(idx, e)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{short}@{{{idx}}} "),
+                    Style::default().fg(Color::Magenta),
+                ),
+                Span::styled(
+                    format!("{:.7}", e.old_id),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw("\u{2192}"),
+                Span::styled(
+                    format!("{:.7} ", e.new_id),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(
+                    format!("{} ", format_time(e.time)),
+                    Style::default().fg(Color::Green),
+                ),
+                Span::raw(e.message.clone()),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!(" Reflog {} ({}) ", view.name, view.entries.len()))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("\u{25b8} ");
+
+    let mut state = ListState::default();
+    state.select(Some(view.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
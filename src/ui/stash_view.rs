@@ -0,0 +1,50 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+use crate::app::StashView;
+use crate::format::format_time;
+
+/// Render the stash list.
+pub fn render(frame: &mut Frame, view: &StashView, area: Rect) {
+    let items: Vec<ListItem> = view
+        .entries
+        .iter()
+        .map(|e| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("stash@{{{}}} ", e.index),
+                    Style::default().fg(Color::Magenta),
+                ),
+                Span::styled(format!("{:.7} ", e.id), Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    format!("{} ", format_time(e.time)),
+                    Style::default().fg(Color::Green),
+                ),
+                Span::raw(e.message.clone()),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!(" Stashes ({}) ", view.entries.len()))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("\u{25b8} ");
+
+    let mut state = ListState::default();
+    state.select(Some(view.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
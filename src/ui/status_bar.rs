@@ -0,0 +1,88 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+use crate::app::{App, View};
+
+/// Render the one-line status bar: the open prompt, a status message, or
+/// the position in the current view.
+pub fn render(frame: &mut Frame, app: &App, area: Rect) {
+    let line = if let Some(prompt) = &app.prompt {
+        Line::from(vec![
+            Span::styled(
+                prompt.kind.label(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(prompt.input.clone()),
+            Span::styled(" ", Style::default().add_modifier(Modifier::REVERSED)),
+        ])
+    } else if let Some(status) = &app.status {
+        let color = if status.is_error {
+            Color::Red
+        } else {
+            Color::Yellow
+        };
+        Line::from(Span::styled(
+            status.text.clone(),
+            Style::default().fg(color),
+        ))
+    } else if let Some(View::Diff(view)) = app.views.last() {
+        let submodule = view
+            .diff
+            .files
+            .get(view.selected_file)
+            .is_some_and(|f| f.submodule.is_some());
+        Line::from(Span::styled(
+            format!(
+                "file {}/{}  [Tab] switch pane  [s] swap direction  {}[q] close",
+                (view.selected_file + 1).min(view.diff.files.len()),
+                view.diff.files.len(),
+                if submodule {
+                    "[Enter] submodule log  "
+                } else {
+                    ""
+                },
+            ),
+            Style::default().fg(Color::DarkGray),
+        ))
+    } else if let Some(View::Rebase(view)) = app.views.last() {
+        let hint = if view.run.is_some() {
+            "resolve and stage conflicts, then [c] continue  [A] abort  [q] leave paused"
+        } else {
+            "[p]ick [r]eword [s]quash [f]ixup [d]rop  [J/K] move  [Enter] run  [q] cancel"
+        };
+        Line::from(Span::styled(hint, Style::default().fg(Color::DarkGray)))
+    } else if let Some(View::Stash(_)) = app.views.last() {
+        Line::from(Span::styled(
+            "[Enter] diff  [a] apply  [p] pop  [d] drop  [q] close",
+            Style::default().fg(Color::DarkGray),
+        ))
+    } else if let Some(View::Reflog(_)) = app.views.last() {
+        Line::from(Span::styled(
+            "[Enter] show commit in log  [q] close",
+            Style::default().fg(Color::DarkGray),
+        ))
+    } else {
+        let total = if app.all_loaded {
+            app.commits.len().to_string()
+        } else {
+            format!("{}+", app.commits.len())
+        };
+        let back = if app.submodule.is_some() {
+            "  [q] back to the parent repository"
+        } else {
+            ""
+        };
+        Line::from(Span::styled(
+            format!("{}/{total}{back}", app.selected + 1),
+            Style::default().fg(Color::DarkGray),
+        ))
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
@@ -0,0 +1,257 @@
+//! Snapshot tests: render the log of small fixture repositories into a
+//! [`TestBackend`] and compare the buffer line by line.
+
+use std::path::Path;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use git2::{Repository, RepositoryInitOptions, Signature, Time};
+use ratatui::{Frame, Terminal, backend::TestBackend};
+use reki::git::{Repo, TrailerFilter};
+use tempfile::TempDir;
+use unicode_width::UnicodeWidthStr;
+
+use super::{diff_view, log_view};
+use crate::app::{App, View};
+
+/// 2024-01-01 00:00 UTC. Tests never set the local offset, so dates show
+/// in UTC.
+const EPOCH: i64 = 1_704_067_200;
+
+/// A repository in a temp dir with one commit per `(author, message)`,
+/// oldest first, a day apart, on `main`.
+fn fixture_repo(commits: &[(&str, &str)]) -> (TempDir, Repo) {
+    let dir = TempDir::new().unwrap();
+    let mut opts = RepositoryInitOptions::new();
+    opts.initial_head("main");
+    let repo = Repository::init_opts(dir.path(), &opts).unwrap();
+
+    let mut parent = None;
+    for (i, (author, message)) in commits.iter().enumerate() {
+        std::fs::write(dir.path().join("file"), format!("{i}\n")).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+        let time = Time::new(EPOCH + i as i64 * 86_400, 0);
+        let sig = Signature::new(author, "dev@example.org", &time).unwrap();
+        let parents: Vec<_> = parent.iter().collect();
+        let id = repo
+            .commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap();
+        parent = Some(repo.find_commit(id).unwrap());
+    }
+
+    let repo = Repo::open(dir.path()).unwrap();
+    (dir, repo)
+}
+
+/// [`fixture_repo`] opened in the viewer.
+fn fixture(commits: &[(&str, &str)]) -> (TempDir, App) {
+    let (dir, repo) = fixture_repo(commits);
+    (dir, App::new(repo).unwrap())
+}
+
+/// Draw the log view alone at `width` x `height` and return the text of
+/// each row, styles left out.
+fn render(app: &mut App, width: u16, height: u16) -> Vec<String> {
+    draw(width, height, |frame| {
+        log_view::render(frame, app, frame.area());
+    })
+}
+
+/// Draw with `f` at `width` x `height` and return the text of each row.
+fn draw(width: u16, height: u16, f: impl FnOnce(&mut Frame)) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(f).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let mut lines = Vec::new();
+    for y in 0..height {
+        let mut line = String::new();
+        let mut hidden = 0;
+        for x in 0..width {
+            // The cells covered by a wide character hold placeholders.
+            if hidden > 0 {
+                hidden -= 1;
+                continue;
+            }
+            let symbol = buffer[(x, y)].symbol();
+            hidden = symbol.width().saturating_sub(1);
+            line.push_str(symbol);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[test]
+fn renders_commit_rows() {
+    let (_dir, mut app) = fixture(&[("Alice", "Initial commit"), ("Bob", "Add feature")]);
+    let lines = render(&mut app, 80, 4);
+    assert_eq!(
+        lines,
+        [
+            "┌ Log (2) ─────────────────────────────────────────────────────────────────────┐",
+            "│▸   * c245b3d   2024-01-02 00:00  Bob              (HEAD) (main) Add feature  │",
+            "│    * 703faac   2024-01-01 00:00  Alice            Initial commit             │",
+            "└──────────────────────────────────────────────────────────────────────────────┘",
+        ]
+    );
+}
+
+#[test]
+fn shows_commit_header_dates_in_utc() {
+    let (_dir, mut app) = fixture(&[("Alice", "Initial commit"), ("Bob", "Add feature")]);
+    app.handle_event(KeyEvent::from(KeyCode::Enter));
+    let Some(View::Diff(view)) = app.views.last() else {
+        panic!("no diff view");
+    };
+    let lines = draw(80, 12, |frame| {
+        diff_view::render(frame, view, frame.area());
+    });
+    for date in [
+        "AuthorDate: 2024-01-02 00:00",
+        "CommitDate: 2024-01-02 00:00",
+    ] {
+        assert!(lines.iter().any(|l| l.contains(date)), "{lines:?}");
+    }
+}
+
+#[test]
+fn aligns_wide_characters() {
+    let (_dir, mut app) = fixture(&[("山田太郎", "日本語のコミット"), ("Zoë", "Add 🚀 launch")]);
+    let lines = render(&mut app, 80, 4);
+    assert_eq!(
+        lines,
+        [
+            "┌ Log (2) ─────────────────────────────────────────────────────────────────────┐",
+            "│▸   * 8d6a22b   2024-01-02 00:00  Zoë              (HEAD) (main) Add 🚀 launch│",
+            "│    * fac3d16   2024-01-01 00:00  山田太郎         日本語のコミット           │",
+            "└──────────────────────────────────────────────────────────────────────────────┘",
+        ]
+    );
+}
+
+#[test]
+fn truncates_long_authors() {
+    let (_dir, mut app) = fixture(&[
+        ("Bartholomew Longname-Smythe", "Short"),
+        ("長い名前の作者さんです", "Wide"),
+    ]);
+    let lines = render(&mut app, 80, 4);
+    assert_eq!(
+        lines,
+        [
+            "┌ Log (2) ─────────────────────────────────────────────────────────────────────┐",
+            "│▸   * b3d8193   2024-01-02 00:00  長い名前の作者…  (HEAD) (main) Wide         │",
+            "│    * af64371   2024-01-01 00:00  Bartholomew Lon… Short                      │",
+            "└──────────────────────────────────────────────────────────────────────────────┘",
+        ]
+    );
+}
+
+#[test]
+fn scrolls_horizontally() {
+    let (_dir, mut app) = fixture(&[("Alice", "First"), ("山田", "漢字 summary")]);
+
+    app.scroll_x = 32;
+    let lines = render(&mut app, 40, 4);
+    assert_eq!(
+        lines,
+        [
+            "┌ Log (2) ─────────────────────────────┐",
+            "│▸ 山田             (HEAD) (main) 漢字 │",
+            "│  Alice            First              │",
+            "└──────────────────────────────────────┘",
+        ]
+    );
+
+    // Half of a wide character scrolled off leaves a blank column.
+    app.scroll_x = 33;
+    let lines = render(&mut app, 40, 4);
+    assert_eq!(
+        lines,
+        [
+            "┌ Log (2) ─────────────────────────────┐",
+            "│▸  田             (HEAD) (main) 漢字 s│",
+            "│  lice            First               │",
+            "└──────────────────────────────────────┘",
+        ]
+    );
+
+    // Past the end, the offset is clamped to the widest row.
+    app.scroll_x = usize::MAX;
+    let lines = render(&mut app, 40, 4);
+    assert_eq!(app.scroll_x, app.max_scroll_x);
+    assert_eq!(
+        lines,
+        [
+            "┌ Log (2) ─────────────────────────────┐",
+            "│▸           (HEAD) (main) 漢字 summary│",
+            "│            First                     │",
+            "└──────────────────────────────────────┘",
+        ]
+    );
+}
+
+#[test]
+fn shows_range_and_filter_in_title() {
+    let (_dir, mut repo) = fixture_repo(&[
+        ("Alice", "First"),
+        ("Alice", "Fix crash\n\nFixes: #12\n"),
+        ("Alice", "Third"),
+    ]);
+    repo.set_range(Some("HEAD~1".to_string())).unwrap();
+    repo.set_filter(TrailerFilter::parse("Fixes"));
+    let mut app = App::new(repo).unwrap();
+    let lines = render(&mut app, 60, 3);
+    assert_eq!(
+        lines,
+        [
+            "┌ Log (1) HEAD~1 [Fixes] ──────────────────────────────────┐",
+            "│▸   * 528b8ae   2024-01-02 00:00  Alice            Fix cra│",
+            "└──────────────────────────────────────────────────────────┘",
+        ]
+    );
+}
+
+#[test]
+fn opens_submodule_logs_and_comes_back() {
+    let (dir, _) = fixture_repo(&[("Alice", "First")]);
+    let (lib_dir, lib) = fixture_repo(&[("Bob", "Lib one"), ("Bob", "Lib two")]);
+    let lib_head = lib.resolve("HEAD").unwrap();
+    std::fs::rename(lib_dir.path(), dir.path().join("lib")).unwrap();
+
+    let repo = Repository::open(dir.path()).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let mut tree = repo.treebuilder(Some(&head.tree().unwrap())).unwrap();
+    let gitlink = git2::Oid::from_bytes(lib_head.as_bytes()).unwrap();
+    tree.insert("lib", gitlink, 0o160000).unwrap();
+    let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+    let mut index = repo.index().unwrap();
+    index.read_tree(&tree).unwrap();
+    index.write().unwrap();
+    let sig = Signature::new("Alice", "dev@example.org", &Time::new(EPOCH, 0)).unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Add lib", &tree, &[&head])
+        .unwrap();
+
+    let mut app = App::new(Repo::open(dir.path()).unwrap()).unwrap();
+    let key = |app: &mut App, code| app.handle_event(KeyEvent::from(code));
+    key(&mut app, KeyCode::Enter);
+    key(&mut app, KeyCode::Enter);
+    assert_eq!(app.submodule.as_deref(), Some("lib"));
+    assert_eq!(app.commits.get(app.selected).unwrap().id(), lib_head);
+    let lines = render(&mut app, 50, 4);
+    assert!(
+        lines[0].starts_with("┌ Log (2) submodule lib ─"),
+        "{lines:?}"
+    );
+
+    key(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.submodule, None);
+    assert_eq!(app.views.len(), 1);
+    key(&mut app, KeyCode::Char('q'));
+    key(&mut app, KeyCode::Char('q'));
+    assert!(app.should_quit);
+}
@@ -0,0 +1,100 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How often the repository files are checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How long the files must stay unchanged before a change is reported, so
+/// that a burst of writes (e.g. a rebase) triggers a single reload.
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Detects changes to HEAD, refs and the index by polling file metadata.
+pub struct RepoWatcher {
+    /// Files whose metadata is fingerprinted.
+    files: Vec<PathBuf>,
+    /// Directories walked recursively (loose refs).
+    dirs: Vec<PathBuf>,
+    fingerprint: u64,
+    last_poll: Instant,
+    /// When a not-yet-reported change was first seen.
+    changed_at: Option<Instant>,
+}
+
+impl RepoWatcher {
+    /// Watch the repository with the given git dir and common dir (they
+    /// differ for linked worktrees).
+    pub fn new(git_dir: &Path, common_dir: &Path) -> Self {
+        let mut watcher = Self {
+            files: vec![
+                git_dir.join("HEAD"),
+                git_dir.join("index"),
+                common_dir.join("packed-refs"),
+            ],
+            dirs: vec![common_dir.join("refs")],
+            fingerprint: 0,
+            last_poll: Instant::now(),
+            changed_at: None,
+        };
+        watcher.fingerprint = watcher.compute();
+        watcher
+    }
+
+    /// Returns `true` once per settled change.
+    pub fn poll(&mut self) -> bool {
+        let interval = if self.changed_at.is_some() {
+            SETTLE_TIME
+        } else {
+            POLL_INTERVAL
+        };
+        if self.last_poll.elapsed() < interval {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        let current = self.compute();
+        if current != self.fingerprint {
+            self.fingerprint = current;
+            self.changed_at = Some(Instant::now());
+            return false;
+        }
+        // Unchanged for a full settle interval since the last change.
+        self.changed_at.take().is_some()
+    }
+
+    /// Hash the size and modification time of every watched file.
+    fn compute(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for file in &self.files {
+            hash_metadata(file, &mut hasher);
+        }
+        for dir in &self.dirs {
+            hash_dir(dir, &mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+fn hash_metadata(path: &Path, hasher: &mut DefaultHasher) {
+    path.hash(hasher);
+    if let Ok(meta) = fs::metadata(path) {
+        meta.len().hash(hasher);
+        meta.modified().ok().hash(hasher);
+    }
+}
+
+fn hash_dir(dir: &Path, hasher: &mut DefaultHasher) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            hash_dir(&path, hasher);
+        } else {
+            hash_metadata(&path, hasher);
+        }
+    }
+}